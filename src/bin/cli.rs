@@ -1,14 +1,19 @@
 use clap::Clap;
+use serde_derive::Deserialize;
 use std::error::Error as StdError;
 use std::fmt;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
-use tokio::signal;
+use tokio::signal::{self, unix::SignalKind};
+use tokio::sync::mpsc;
 use tokio::sync::oneshot::{self, Receiver, Sender};
 use url::Url;
 
 use elasticsearch_exporter::{
-    CollectionLabels, ExporterMetricsSwitch, ExporterPollIntervals, Labels,
+    credentials::{Auth, Secret, Tls},
+    ClusterOptions, CollectionLabels, ExporterMetricsSwitch, ExporterOptions,
+    ExporterPollIntervals, Labels,
 };
 
 pub fn unit_channel() -> (Sender<()>, Receiver<()>) {
@@ -30,6 +35,31 @@ pub fn signal_channel() -> Receiver<()> {
     signal_rx
 }
 
+/// Notify on every SIGHUP signal
+async fn wait_for_hangup(tx: mpsc::Sender<()>) {
+    let mut hangup = match signal::unix::signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!("SIGHUP handler err {}", e);
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received: reloading configuration");
+        // Reload already pending covers this signal too
+        let _ = tx.try_send(());
+    }
+}
+
+pub fn reload_channel() -> mpsc::Receiver<()> {
+    let (reload_tx, reload_rx) = mpsc::channel(1);
+
+    let _ = tokio::spawn(wait_for_hangup(reload_tx));
+
+    reload_rx
+}
+
 #[derive(Debug)]
 pub struct SimpleError(String);
 
@@ -61,10 +91,128 @@ pub struct Opts {
     #[clap(long = "hyper_http2_keep_alive_timeout", default_value = "1m")]
     pub hyper_http2_keep_alive_timeout: humantime::Duration,
 
+    /// Web configuration file with listener TLS and basic authentication,
+    /// Prometheus exporter-toolkit format
+    #[clap(long = "web.config.file")]
+    pub web_config_file: Option<PathBuf>,
+
+    /// Exporter configuration file in YAML or TOML (by `.toml` extension) format,
+    /// explicitly provided flags take precedence over configuration file
+    #[clap(long = "config")]
+    pub config: Option<PathBuf>,
+
+    /// Interval of checking configuration, web configuration and listener certificate files
+    /// modification time, configuration is reloaded on change. Configuration is reloaded
+    /// on SIGHUP regardless of this flag
+    #[clap(long = "config_watch_interval")]
+    pub config_watch_interval: Option<humantime::Duration>,
+
+    /// Enable /subsystems admin endpoint for starting and stopping subsystems at runtime
+    #[clap(long = "exporter_admin_enabled")]
+    pub exporter_admin_enabled: bool,
+
+    /// Enable /probe?target=<url>&module=<subsystem> endpoint for scraping
+    /// given cluster on demand, blackbox exporter style
+    #[clap(long = "exporter_probe_enabled")]
+    pub exporter_probe_enabled: bool,
+
     /// Elasticsearch URL, provide with protocol "https?://"
     #[clap(long = "elasticsearch_url", default_value = "http://127.0.0.1:9200")]
     pub elasticsearch_url: Url,
 
+    /// Additional seed URL of the same cluster, can be repeated, requests are
+    /// round-robined across alive nodes
+    #[clap(
+        long = "elasticsearch_seed_url",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    pub elasticsearch_seed_url: Vec<Url>,
+
+    /// Discover cluster nodes via /_nodes/http and use them instead of seed URLs
+    #[clap(long = "elasticsearch_sniff")]
+    pub elasticsearch_sniff: bool,
+
+    /// Interval of connection pool health checks and sniffing, dead nodes are
+    /// retried on every check
+    #[clap(long = "elasticsearch_pool_check_interval", default_value = "30s")]
+    pub elasticsearch_pool_check_interval: humantime::Duration,
+
+    /// Elasticsearch cluster scraped in multi-cluster mode, can be repeated, e.g.:
+    /// `url=https://es-a:9200&name=es-a&seed_urls[0]=https://es-a-2:9200&metrics_enabled[cat_health]=true&poll_intervals[cat_health]=5s&subsystem_timeouts[cat_health]=3s`
+    /// when provided `elasticsearch_url` is not scraped. Cluster credentials and TLS override
+    /// exporter ones, e.g.: `username=exporter&password_file=/run/secrets/es-a&ca_cert=/etc/es-a/ca.pem`
+    #[clap(
+        long = "elasticsearch_cluster",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    pub elasticsearch_cluster: Vec<ClusterArg>,
+
+    /// Elasticsearch basic authentication username
+    #[clap(long = "elasticsearch_username", conflicts_with_all = &["elasticsearch-api-key", "elasticsearch-api-key-file", "elasticsearch-bearer-token", "elasticsearch-bearer-token-file"])]
+    pub elasticsearch_username: Option<String>,
+
+    /// Elasticsearch basic authentication password
+    #[clap(long = "elasticsearch_password", requires = "elasticsearch-username")]
+    pub elasticsearch_password: Option<String>,
+
+    /// Elasticsearch basic authentication password file, re-read on change
+    #[clap(
+        long = "elasticsearch_password_file",
+        requires = "elasticsearch-username",
+        conflicts_with = "elasticsearch-password"
+    )]
+    pub elasticsearch_password_file: Option<PathBuf>,
+
+    /// Elasticsearch API key in `id:api_key` format
+    #[clap(long = "elasticsearch_api_key", conflicts_with_all = &["elasticsearch-api-key-file", "elasticsearch-bearer-token", "elasticsearch-bearer-token-file"])]
+    pub elasticsearch_api_key: Option<String>,
+
+    /// Elasticsearch API key file with `id:api_key` content, re-read on change
+    #[clap(long = "elasticsearch_api_key_file", conflicts_with_all = &["elasticsearch-bearer-token", "elasticsearch-bearer-token-file"])]
+    pub elasticsearch_api_key_file: Option<PathBuf>,
+
+    /// Elasticsearch bearer token
+    #[clap(
+        long = "elasticsearch_bearer_token",
+        conflicts_with = "elasticsearch-bearer-token-file"
+    )]
+    pub elasticsearch_bearer_token: Option<String>,
+
+    /// Elasticsearch bearer token file, re-read on change
+    #[clap(long = "elasticsearch_bearer_token_file")]
+    pub elasticsearch_bearer_token_file: Option<PathBuf>,
+
+    /// CA certificate bundle in PEM format used to verify Elasticsearch certificate
+    #[clap(long = "elasticsearch_ca_cert")]
+    pub elasticsearch_ca_cert: Option<PathBuf>,
+
+    /// Client certificate in PEM format for mutual TLS
+    #[clap(
+        long = "elasticsearch_client_cert",
+        requires = "elasticsearch-client-key"
+    )]
+    pub elasticsearch_client_cert: Option<PathBuf>,
+
+    /// Client certificate key in PEM format for mutual TLS
+    #[clap(
+        long = "elasticsearch_client_key",
+        requires = "elasticsearch-client-cert"
+    )]
+    pub elasticsearch_client_key: Option<PathBuf>,
+
+    /// Skip Elasticsearch certificate verification, do not use in production
+    #[clap(long = "elasticsearch_insecure")]
+    pub elasticsearch_insecure: bool,
+
+    /// Interval of checking credential and certificate files for changes
+    #[clap(
+        long = "elasticsearch_credentials_refresh_interval",
+        default_value = "30s"
+    )]
+    pub elasticsearch_credentials_refresh_interval: humantime::Duration,
+
     /// Elasticsearch global timeout of all metrics
     #[clap(long = "elasticsearch_global_timeout", default_value = "30s")]
     pub elasticsearch_global_timeout: humantime::Duration,
@@ -95,7 +243,7 @@ pub struct Opts {
     /// Exporter include labels
     #[clap(
         long = "exporter_include_labels",
-        default_value = "cat_health=shards&cat_aliases=index,alias&cat_allocation=node&cat_fielddata=node,field&cat_indices=index&cat_nodeattrs=node,attr&cat_nodes=ip,name,node_role&cat_pending_tasks=index&cat_plugins=name&cat_recovery=index,shard,stage,type&cat_repositories=index&cat_segments=index,shard&cat_shards=index,node,shard&cat_templates=name,index_patterns&cat_thread_pool=node_name,name,type&cat_transforms=index&cluster_health=status&nodes_usage=name&nodes_stats=name,vin_cluster_version&nodes_info=name&stats=index"
+        default_value = "canary=shard,name&cat_health=shards&cat_aliases=index,alias&cat_allocation=node&cat_fielddata=node,field&cat_indices=index&cat_nodeattrs=node,attr&cat_nodes=ip,name,node_role&cat_pending_tasks=index&cat_plugins=name&cat_recovery=index,shard,stage,type&cat_repositories=index&cat_segments=index,shard&cat_shards=index,node,shard&cat_templates=name,index_patterns&cat_thread_pool=node_name,name,type&cat_transforms=index&cluster_health=status&cluster_pending_tasks=priority,source&cluster_state=name,prirep,reason,state,master_node&allocation_explain=reason,can_allocate,decider&remote_info=remote,mode&cluster_settings=setting,source,state&ccr=remote_cluster,leader_index,follower_index,shard&cluster_stats=name,version,pretty_name,flavor,type,arch&data_streams=data_stream,color,ilm_policy&index_freshness=pattern&index_blocks=block,index&queries=query,aggregation,key,metric&ilm=policy,phase,step,failed_step&ilm_indices=index,policy,phase,action,step,failed_step&deprecations=category,level&license=type,status&nodes_versions=version&nodes_usage=name&nodes_stats=name,vin_cluster_version,pipeline,processor_type,processor_tag&nodes_info=name&snapshots=policy,repository,status,snapshot,state&ssl_certificates=path,subject,serial&stats=index&tasks=action,name,cancellable"
     )]
     pub exporter_include_labels: HashMapVec,
 
@@ -130,6 +278,68 @@ pub struct Opts {
     #[clap(long = "exporter_metadata_refresh_interval", default_value = "3m")]
     pub exporter_metadata_refresh_interval: humantime::Duration,
 
+    /// Tasks running longer than threshold are counted by tasks subsystem
+    /// as long running, e.g.: runaway reindex or delete by query
+    #[clap(long = "exporter_tasks_long_running_threshold", default_value = "5m")]
+    pub exporter_tasks_long_running_threshold: humantime::Duration,
+
+    /// Maximum number of unassigned shards sampled from /_cat/shards and explained
+    /// by allocation_explain subsystem per poll
+    #[clap(long = "exporter_allocation_explain_max_shards", default_value = "10")]
+    pub exporter_allocation_explain_max_shards: usize,
+
+    /// Export per processor ingest pipeline stats in nodes_stats subsystem, requires
+    /// `ingest` in nodes_stats path parameters
+    #[clap(long = "exporter_nodes_stats_ingest_processors")]
+    pub exporter_nodes_stats_ingest_processors: bool,
+
+    /// Timestamp field used by index_freshness subsystem to find the newest
+    /// document of each pattern configured in path parameters
+    #[clap(
+        long = "exporter_index_freshness_timestamp_field",
+        default_value = "@timestamp"
+    )]
+    pub exporter_index_freshness_timestamp_field: String,
+
+    /// Window of recent documents counted by index_freshness subsystem
+    #[clap(long = "exporter_index_freshness_window", default_value = "5m")]
+    pub exporter_index_freshness_window: humantime::Duration,
+
+    /// Maximum number of aggregation buckets exported per query of queries subsystem,
+    /// queries are defined in configuration file
+    #[clap(long = "exporter_queries_max_buckets", default_value = "100")]
+    pub exporter_queries_max_buckets: usize,
+
+    /// Index canary subsystem writes documents to, index is created unless it exists
+    #[clap(
+        long = "exporter_canary_index",
+        default_value = "elasticsearch-exporter-canary"
+    )]
+    pub exporter_canary_index: String,
+
+    /// Number of shards canary index is created with, canary document is routed
+    /// to every primary shard, e.g.: number of data nodes
+    #[clap(long = "exporter_canary_shards", default_value = "1")]
+    pub exporter_canary_shards: usize,
+
+    /// Time canary document must become visible to search within,
+    /// otherwise visibility failure is counted
+    #[clap(long = "exporter_canary_visibility_timeout", default_value = "10s")]
+    pub exporter_canary_visibility_timeout: humantime::Duration,
+
+    /// Canary documents older than retention are deleted once per retention interval
+    #[clap(long = "exporter_canary_retention", default_value = "1h")]
+    pub exporter_canary_retention: humantime::Duration,
+
+    /// Comma-separated cluster settings exported by cluster_settings subsystem,
+    /// keys ending with * match by prefix
+    #[clap(
+        long = "exporter_cluster_settings",
+        use_delimiter = true,
+        default_value = "cluster.routing.allocation.enable,cluster.routing.rebalance.enable,cluster.routing.allocation.disk.*,cluster.blocks.*,cluster.max_shards_per_node,indices.recovery.max_bytes_per_sec"
+    )]
+    pub exporter_cluster_settings: Vec<String>,
+
     /// Elasticsearch query ?fields= for /_nodes/stats fields comma-separated list or
     /// wildcard expressions of fields to include in the statistics.
     #[clap(long = "elasticsearch_query_fields", default_value = "nodes_stats=*")]
@@ -152,6 +362,115 @@ pub struct Opts {
     pub exporter_metrics_lifetime_interval: HashMapDuration,
 }
 
+impl Opts {
+    /// Exporter options from provided flags
+    pub fn exporter_options(&self) -> ExporterOptions {
+        ExporterOptions {
+            elasticsearch_url: self.elasticsearch_url.clone(),
+            elasticsearch_cluster_name: None,
+            elasticsearch_clusters: self
+                .elasticsearch_cluster
+                .iter()
+                .map(|cluster| cluster.0.clone())
+                .collect(),
+            elasticsearch_seed_urls: self.elasticsearch_seed_url.clone(),
+            elasticsearch_sniff: self.elasticsearch_sniff,
+            elasticsearch_pool_check_interval: *self.elasticsearch_pool_check_interval,
+            elasticsearch_auth: self.elasticsearch_auth(),
+            elasticsearch_tls: self.elasticsearch_tls(),
+            elasticsearch_credentials_refresh_interval: *self
+                .elasticsearch_credentials_refresh_interval,
+            elasticsearch_global_timeout: *self.elasticsearch_global_timeout,
+            elasticsearch_query_fields: self.elasticsearch_query_fields.0.clone(),
+            elasticsearch_subsystem_timeouts: self.elasticsearch_subsystem_timeouts.0.clone(),
+            elasticsearch_path_parameters: self.elasticsearch_path_parameters.0.clone(),
+
+            exporter_skip_labels: self.exporter_skip_labels.0.clone(),
+            exporter_skip_metrics: self.exporter_skip_metrics.0.clone(),
+            exporter_include_labels: self.exporter_include_labels.0.clone(),
+            exporter_poll_default_interval: *self.exporter_poll_default_interval,
+            exporter_skip_zero_metrics: !self.exporter_allow_zero_metrics,
+            exporter_poll_intervals: self.exporter_poll_intervals.0.clone(),
+            exporter_metrics_enabled: self.exporter_metrics_enabled.0.clone(),
+            exporter_metadata_refresh_interval: *self.exporter_metadata_refresh_interval,
+            exporter_tasks_long_running_threshold: *self.exporter_tasks_long_running_threshold,
+            exporter_allocation_explain_max_shards: self.exporter_allocation_explain_max_shards,
+            exporter_nodes_stats_ingest_processors: self.exporter_nodes_stats_ingest_processors,
+            exporter_index_freshness_timestamp_field: self
+                .exporter_index_freshness_timestamp_field
+                .clone(),
+            exporter_index_freshness_window: *self.exporter_index_freshness_window,
+            exporter_queries: vec![],
+            exporter_queries_max_buckets: self.exporter_queries_max_buckets,
+            exporter_canary_index: self.exporter_canary_index.clone(),
+            exporter_canary_shards: self.exporter_canary_shards,
+            exporter_canary_visibility_timeout: *self.exporter_canary_visibility_timeout,
+            exporter_canary_retention: *self.exporter_canary_retention,
+            exporter_cluster_settings: self.exporter_cluster_settings.clone(),
+
+            exporter_metrics_lifetime_interval: self.exporter_metrics_lifetime_interval.0.clone(),
+            exporter_metrics_lifetime_default_interval: *self
+                .exporter_metrics_lifetime_default_interval,
+        }
+    }
+
+    /// Elasticsearch client TLS configuration from provided flags
+    pub fn elasticsearch_tls(&self) -> Tls {
+        Tls {
+            ca_cert: self.elasticsearch_ca_cert.clone(),
+            client_cert: self.elasticsearch_client_cert.clone(),
+            client_key: self.elasticsearch_client_key.clone(),
+            insecure: self.elasticsearch_insecure,
+        }
+    }
+
+    /// Elasticsearch authentication from provided flags
+    pub fn elasticsearch_auth(&self) -> Option<Auth> {
+        auth(
+            &self.elasticsearch_username,
+            secret(
+                &self.elasticsearch_password,
+                &self.elasticsearch_password_file,
+            ),
+            secret(
+                &self.elasticsearch_api_key,
+                &self.elasticsearch_api_key_file,
+            ),
+            secret(
+                &self.elasticsearch_bearer_token,
+                &self.elasticsearch_bearer_token_file,
+            ),
+        )
+    }
+}
+
+fn secret(value: &Option<String>, file: &Option<PathBuf>) -> Option<Secret> {
+    match (value, file) {
+        (Some(value), _) => Some(Secret::Value(value.clone())),
+        (None, Some(file)) => Some(Secret::File(file.clone())),
+        (None, None) => None,
+    }
+}
+
+/// Basic authentication takes precedence over API key and bearer token
+fn auth(
+    username: &Option<String>,
+    password: Option<Secret>,
+    api_key: Option<Secret>,
+    bearer_token: Option<Secret>,
+) -> Option<Auth> {
+    if let Some(ref username) = username {
+        return Some(Auth::Basic {
+            username: username.clone(),
+            password: password.unwrap_or_else(|| Secret::Value(String::new())),
+        });
+    }
+
+    api_key
+        .map(Auth::ApiKey)
+        .or_else(|| bearer_token.map(Auth::Bearer))
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct HashMapSwitch(pub ExporterMetricsSwitch);
 
@@ -243,6 +562,131 @@ impl FromStr for HashMapVec {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct ClusterQuery {
+    url: String,
+    name: Option<String>,
+    #[serde(default)]
+    seed_urls: Vec<String>,
+    #[serde(default)]
+    metrics_enabled: ExporterMetricsSwitch,
+    #[serde(default)]
+    poll_intervals: Labels,
+    #[serde(default)]
+    subsystem_timeouts: Labels,
+    username: Option<String>,
+    password: Option<String>,
+    password_file: Option<PathBuf>,
+    api_key: Option<String>,
+    api_key_file: Option<PathBuf>,
+    bearer_token: Option<String>,
+    bearer_token_file: Option<PathBuf>,
+    ca_cert: Option<PathBuf>,
+    client_cert: Option<PathBuf>,
+    client_key: Option<PathBuf>,
+    insecure: Option<bool>,
+}
+
+impl ClusterQuery {
+    /// Cluster authentication, only one authentication method is allowed
+    fn auth(&self) -> Result<Option<Auth>, SimpleError> {
+        let password = secret(&self.password, &self.password_file);
+        let api_key = secret(&self.api_key, &self.api_key_file);
+        let bearer_token = secret(&self.bearer_token, &self.bearer_token_file);
+
+        let methods = [
+            self.username.is_some(),
+            api_key.is_some(),
+            bearer_token.is_some(),
+        ];
+        if methods.iter().filter(|method| **method).count() > 1 {
+            return Err(SimpleError(
+                "Only one of username, api_key or bearer_token is allowed".into(),
+            ));
+        }
+
+        Ok(auth(&self.username, password, api_key, bearer_token))
+    }
+
+    /// Cluster TLS configuration, if any TLS parameter is provided
+    fn tls(&self) -> Option<Tls> {
+        if self.ca_cert.is_none()
+            && self.client_cert.is_none()
+            && self.client_key.is_none()
+            && self.insecure.is_none()
+        {
+            return None;
+        }
+
+        Some(Tls {
+            ca_cert: self.ca_cert.clone(),
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+            insecure: self.insecure.unwrap_or(false),
+        })
+    }
+}
+
+fn parse_durations(labels: Labels) -> Result<ExporterPollIntervals, SimpleError> {
+    let mut map = ExporterPollIntervals::new();
+
+    for (key, value) in labels.into_iter() {
+        match value.parse::<humantime::Duration>() {
+            Ok(time) => {
+                let _ = map.insert(key, *time);
+            }
+            Err(e) => {
+                return Err(SimpleError(format!(
+                    "Failed to parse time for key {} value {} err {}",
+                    key, value, e
+                )))
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+#[derive(Clone, Debug)]
+pub struct ClusterArg(pub ClusterOptions);
+
+impl FromStr for ClusterArg {
+    type Err = SimpleError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let query: ClusterQuery = serde_qs::from_str(input).map_err(|e| {
+            SimpleError(format!(
+                "Usage `url=https://127.0.0.1:9200&name=es&metrics_enabled[cat_health]=true&poll_intervals[cat_health]=5s`, you provided `{}`",
+                e
+            ))
+        })?;
+
+        let url = Url::parse(&query.url)
+            .map_err(|e| SimpleError(format!("Failed to parse url {} err {}", query.url, e)))?;
+
+        let mut seed_urls = Vec::new();
+        for seed_url in query.seed_urls.iter() {
+            seed_urls.push(Url::parse(seed_url).map_err(|e| {
+                SimpleError(format!("Failed to parse seed url {} err {}", seed_url, e))
+            })?);
+        }
+
+        let auth = query.auth()?;
+        let tls = query.tls();
+
+        Ok(Self(ClusterOptions {
+            elasticsearch_url: url,
+            elasticsearch_cluster_name: query.name,
+            elasticsearch_seed_urls: seed_urls,
+            elasticsearch_auth: auth,
+            elasticsearch_tls: tls,
+            exporter_metrics_enabled: query.metrics_enabled,
+            exporter_poll_intervals: parse_durations(query.poll_intervals)?,
+            elasticsearch_subsystem_timeouts: parse_durations(query.subsystem_timeouts)?,
+        }))
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 struct HashMapStr(pub Labels);
 
@@ -258,3 +702,79 @@ impl FromStr for HashMapStr {
         })?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_arg() {
+        let cluster = ClusterArg::from_str(
+            "url=https://es-a:9200&name=es-a&seed_urls[0]=https://es-a-2:9200&username=exporter&password_file=/secrets/es-a&ca_cert=/certs/es-a.pem&metrics_enabled[cat_health]=true&poll_intervals[cat_health]=5s&subsystem_timeouts[cat_health]=3s",
+        )
+        .expect("valid cluster")
+        .0;
+
+        assert_eq!(cluster.elasticsearch_url.as_str(), "https://es-a:9200/");
+        assert_eq!(cluster.elasticsearch_cluster_name.as_deref(), Some("es-a"));
+        assert_eq!(
+            cluster.elasticsearch_seed_urls,
+            vec![Url::parse("https://es-a-2:9200").unwrap()]
+        );
+        assert_eq!(
+            cluster.elasticsearch_auth,
+            Some(Auth::Basic {
+                username: "exporter".into(),
+                password: Secret::File("/secrets/es-a".into()),
+            })
+        );
+        assert_eq!(
+            cluster.elasticsearch_tls,
+            Some(Tls {
+                ca_cert: Some("/certs/es-a.pem".into()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            cluster.exporter_metrics_enabled.get("cat_health"),
+            Some(&true)
+        );
+        assert_eq!(
+            cluster.exporter_poll_intervals.get("cat_health"),
+            Some(&std::time::Duration::from_secs(5))
+        );
+        assert_eq!(
+            cluster.elasticsearch_subsystem_timeouts.get("cat_health"),
+            Some(&std::time::Duration::from_secs(3))
+        );
+
+        let cluster = ClusterArg::from_str("url=http://es-b:9200")
+            .expect("valid cluster")
+            .0;
+        assert_eq!(cluster.elasticsearch_auth, None);
+        assert_eq!(cluster.elasticsearch_tls, None);
+
+        assert!(ClusterArg::from_str("url=http://es-b:9200&username=a&api_key=b").is_err());
+        assert!(ClusterArg::from_str("name=es-b").is_err());
+        assert!(
+            ClusterArg::from_str("url=http://es-b:9200&poll_intervals[cat_health]=often").is_err()
+        );
+    }
+
+    #[test]
+    fn test_seed_urls() {
+        let opts = Opts::parse_from([
+            "elasticsearch_exporter",
+            "--elasticsearch_seed_url=http://es-2:9200",
+            "--elasticsearch_seed_url=http://es-3:9200",
+        ]);
+
+        assert_eq!(
+            opts.elasticsearch_seed_url,
+            vec![
+                Url::parse("http://es-2:9200").unwrap(),
+                Url::parse("http://es-3:9200").unwrap()
+            ]
+        );
+    }
+}
@@ -0,0 +1,582 @@
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use url::Url;
+
+use elasticsearch_exporter::{
+    credentials::{Auth, Tls},
+    ClusterOptions, ExporterOptions, QueryOptions,
+};
+
+/// Exporter configuration file, keys mirror `ExporterOptions` while per-subsystem
+/// options are grouped under `subsystems`, e.g.:
+///
+/// ```yaml
+/// elasticsearch_url: http://127.0.0.1:9200
+/// subsystems:
+///   cat_indices:
+///     enabled: true
+///     interval: 30s
+///     include_labels: [index]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    elasticsearch_url: Option<String>,
+    elasticsearch_seed_urls: Option<Vec<String>>,
+    elasticsearch_sniff: Option<bool>,
+    #[serde(default, with = "humantime_serde")]
+    elasticsearch_pool_check_interval: Option<Duration>,
+    elasticsearch_auth: Option<Auth>,
+    elasticsearch_tls: Option<Tls>,
+    #[serde(default, with = "humantime_serde")]
+    elasticsearch_credentials_refresh_interval: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    elasticsearch_global_timeout: Option<Duration>,
+    elasticsearch_clusters: Option<Vec<ClusterConfig>>,
+
+    exporter_skip_zero_metrics: Option<bool>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_poll_default_interval: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_metrics_lifetime_default_interval: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_metadata_refresh_interval: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_tasks_long_running_threshold: Option<Duration>,
+    exporter_allocation_explain_max_shards: Option<usize>,
+    exporter_nodes_stats_ingest_processors: Option<bool>,
+    exporter_index_freshness_timestamp_field: Option<String>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_index_freshness_window: Option<Duration>,
+    exporter_queries: Option<Vec<QueryOptions>>,
+    exporter_queries_max_buckets: Option<usize>,
+    exporter_canary_index: Option<String>,
+    exporter_canary_shards: Option<usize>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_canary_visibility_timeout: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    exporter_canary_retention: Option<Duration>,
+    exporter_cluster_settings: Option<Vec<String>>,
+
+    #[serde(default)]
+    subsystems: BTreeMap<String, SubsystemConfig>,
+}
+
+/// Subsystem block of configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SubsystemConfig {
+    enabled: Option<bool>,
+    #[serde(default, with = "humantime_serde")]
+    interval: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    timeout: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    lifetime: Option<Duration>,
+    skip_labels: Option<Vec<String>>,
+    include_labels: Option<Vec<String>>,
+    skip_metrics: Option<Vec<String>>,
+    path_parameters: Option<Vec<String>>,
+    fields: Option<Vec<String>>,
+}
+
+/// Cluster of multi-cluster mode
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClusterConfig {
+    url: String,
+    name: Option<String>,
+    #[serde(default)]
+    seed_urls: Vec<String>,
+    auth: Option<Auth>,
+    tls: Option<Tls>,
+    #[serde(default)]
+    subsystems: BTreeMap<String, ClusterSubsystemConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClusterSubsystemConfig {
+    enabled: Option<bool>,
+    #[serde(default, with = "humantime_serde")]
+    interval: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    timeout: Option<Duration>,
+}
+
+fn parse_url(key: &str, url: &str) -> Result<Url, String> {
+    Url::parse(url).map_err(|e| format!("{}: invalid url {} err {}", key, url, e))
+}
+
+fn check_subsystem(key: &str, subsystem: &str) -> Result<(), String> {
+    if ExporterOptions::subsystems().contains(&subsystem) {
+        Ok(())
+    } else {
+        Err(format!("{}.{}: unknown subsystem", key, subsystem))
+    }
+}
+
+/// Merge per-subsystem file values into options map, explicit CLI flag
+/// values win over file values of the same subsystem
+macro_rules! merge_subsystems {
+    ($target:expr, $values:expr, $explicit:expr) => {
+        for (subsystem, value) in $values {
+            if !$explicit || !$target.contains_key(&subsystem) {
+                let _ = $target.insert(subsystem, value);
+            }
+        }
+    };
+}
+
+fn subsystem_values<T>(
+    subsystems: &BTreeMap<String, SubsystemConfig>,
+    field: impl Fn(&SubsystemConfig) -> Option<T>,
+) -> Vec<(String, T)> {
+    subsystems
+        .iter()
+        .filter_map(|(subsystem, config)| field(config).map(|value| (subsystem.clone(), value)))
+        .collect()
+}
+
+fn deserialize<T: DeserializeOwned>(path: &Path, content: &str) -> Result<T, String> {
+    let is_toml = path
+        .extension()
+        .map(|extension| extension == "toml")
+        .unwrap_or(false);
+
+    if is_toml {
+        serde_path_to_error::deserialize(&mut toml::Deserializer::new(content))
+            .map_err(|e| format!("{}: {}", e.path(), e.inner()))
+    } else {
+        serde_path_to_error::deserialize(serde_yaml::Deserializer::from_str(content))
+            .map_err(|e| format!("{}: {}", e.path(), e.inner()))
+    }
+}
+
+impl Config {
+    /// Load YAML or TOML (by `.toml` extension) configuration file
+    pub fn load(path: &Path) -> Result<Self, Box<dyn StdError>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {} err {}", path.display(), e))?;
+
+        Ok(deserialize(path, &content)
+            .map_err(|e| format!("Failed to parse {} {}", path.display(), e))?)
+    }
+
+    /// Apply configuration file over options, `explicit` reports whether CLI flag
+    /// was provided explicitly, such flags override configuration file
+    pub fn apply(
+        self,
+        options: &mut ExporterOptions,
+        explicit: impl Fn(&str) -> bool,
+    ) -> Result<(), Box<dyn StdError>> {
+        macro_rules! set {
+            ($field:ident) => {
+                set!($field, $field)
+            };
+            ($field:ident, $flag:ident) => {
+                if let Some(value) = self.$field {
+                    if !explicit(stringify!($flag)) {
+                        options.$field = value;
+                    }
+                }
+            };
+        }
+
+        if let Some(ref url) = self.elasticsearch_url {
+            if !explicit("elasticsearch_url") {
+                options.elasticsearch_url = parse_url("elasticsearch_url", url)?;
+            }
+        }
+
+        if let Some(ref urls) = self.elasticsearch_seed_urls {
+            if !explicit("elasticsearch_seed_url") {
+                options.elasticsearch_seed_urls = urls
+                    .iter()
+                    .map(|url| parse_url("elasticsearch_seed_urls", url))
+                    .collect::<Result<Vec<Url>, String>>()?;
+            }
+        }
+
+        set!(elasticsearch_sniff);
+        set!(elasticsearch_pool_check_interval);
+        set!(elasticsearch_credentials_refresh_interval);
+        set!(elasticsearch_global_timeout);
+        set!(exporter_poll_default_interval);
+        set!(exporter_metrics_lifetime_default_interval);
+        set!(exporter_metadata_refresh_interval);
+        set!(exporter_tasks_long_running_threshold);
+        set!(exporter_allocation_explain_max_shards);
+        set!(exporter_nodes_stats_ingest_processors);
+        set!(exporter_index_freshness_timestamp_field);
+        set!(exporter_index_freshness_window);
+        set!(exporter_queries_max_buckets);
+        set!(exporter_canary_index);
+        set!(exporter_canary_shards);
+        set!(exporter_canary_visibility_timeout);
+        set!(exporter_canary_retention);
+        set!(exporter_cluster_settings);
+
+        // Queries are configured only in configuration file
+        if let Some(queries) = self.exporter_queries {
+            for (i, query) in queries.iter().enumerate() {
+                if queries[..i].iter().any(|other| other.name == query.name) {
+                    return Err(
+                        format!("exporter_queries.{}: duplicate query name", query.name).into(),
+                    );
+                }
+            }
+            options.exporter_queries = queries;
+        }
+
+        if let Some(skip_zero_metrics) = self.exporter_skip_zero_metrics {
+            if !explicit("exporter_allow_zero_metrics") {
+                options.exporter_skip_zero_metrics = skip_zero_metrics;
+            }
+        }
+
+        let auth_flags = [
+            "elasticsearch_username",
+            "elasticsearch_api_key",
+            "elasticsearch_api_key_file",
+            "elasticsearch_bearer_token",
+            "elasticsearch_bearer_token_file",
+        ];
+        if self.elasticsearch_auth.is_some() && !auth_flags.iter().any(|flag| explicit(flag)) {
+            options.elasticsearch_auth = self.elasticsearch_auth;
+        }
+
+        let tls_flags = [
+            "elasticsearch_ca_cert",
+            "elasticsearch_client_cert",
+            "elasticsearch_client_key",
+            "elasticsearch_insecure",
+        ];
+        if let Some(tls) = self.elasticsearch_tls {
+            if !tls_flags.iter().any(|flag| explicit(flag)) {
+                options.elasticsearch_tls = tls;
+            }
+        }
+
+        if let Some(clusters) = self.elasticsearch_clusters {
+            if !explicit("elasticsearch_cluster") {
+                options.elasticsearch_clusters = clusters
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, cluster)| cluster.into_options(index))
+                    .collect::<Result<Vec<ClusterOptions>, String>>()?;
+            }
+        }
+
+        for subsystem in self.subsystems.keys() {
+            check_subsystem("subsystems", subsystem)?;
+        }
+
+        let subsystems = self.subsystems;
+
+        merge_subsystems!(
+            options.exporter_metrics_enabled,
+            subsystem_values(&subsystems, |config| config.enabled),
+            explicit("exporter_metrics_enabled")
+        );
+        merge_subsystems!(
+            options.exporter_poll_intervals,
+            subsystem_values(&subsystems, |config| config.interval),
+            explicit("exporter_poll_intervals")
+        );
+        merge_subsystems!(
+            options.elasticsearch_subsystem_timeouts,
+            subsystem_values(&subsystems, |config| config.timeout),
+            explicit("elasticsearch_subsystem_timeouts")
+        );
+        merge_subsystems!(
+            options.exporter_metrics_lifetime_interval,
+            subsystem_values(&subsystems, |config| config.lifetime),
+            explicit("exporter_metrics_lifetime_interval")
+        );
+        merge_subsystems!(
+            options.exporter_skip_labels,
+            subsystem_values(&subsystems, |config| config.skip_labels.clone()),
+            explicit("exporter_skip_labels")
+        );
+        merge_subsystems!(
+            options.exporter_include_labels,
+            subsystem_values(&subsystems, |config| config.include_labels.clone()),
+            explicit("exporter_include_labels")
+        );
+        merge_subsystems!(
+            options.exporter_skip_metrics,
+            subsystem_values(&subsystems, |config| config.skip_metrics.clone()),
+            explicit("exporter_skip_metrics")
+        );
+        merge_subsystems!(
+            options.elasticsearch_path_parameters,
+            subsystem_values(&subsystems, |config| config.path_parameters.clone()),
+            explicit("elasticsearch_path_parameters")
+        );
+        merge_subsystems!(
+            options.elasticsearch_query_fields,
+            subsystem_values(&subsystems, |config| config.fields.clone()),
+            explicit("elasticsearch_query_fields")
+        );
+
+        Ok(())
+    }
+}
+
+impl ClusterConfig {
+    fn into_options(self, index: usize) -> Result<ClusterOptions, String> {
+        let key = format!("elasticsearch_clusters[{}]", index);
+        let mut options = ClusterOptions {
+            elasticsearch_url: parse_url(&format!("{}.url", key), &self.url)?,
+            elasticsearch_cluster_name: self.name,
+            elasticsearch_seed_urls: self
+                .seed_urls
+                .iter()
+                .map(|url| parse_url(&format!("{}.seed_urls", key), url))
+                .collect::<Result<Vec<Url>, String>>()?,
+            elasticsearch_auth: self.auth,
+            elasticsearch_tls: self.tls,
+            exporter_metrics_enabled: Default::default(),
+            exporter_poll_intervals: Default::default(),
+            elasticsearch_subsystem_timeouts: Default::default(),
+        };
+
+        for (subsystem, config) in self.subsystems.into_iter() {
+            check_subsystem(&format!("{}.subsystems", key), &subsystem)?;
+
+            if let Some(enabled) = config.enabled {
+                let _ = options
+                    .exporter_metrics_enabled
+                    .insert(subsystem.clone(), enabled);
+            }
+            if let Some(interval) = config.interval {
+                let _ = options
+                    .exporter_poll_intervals
+                    .insert(subsystem.clone(), interval);
+            }
+            if let Some(timeout) = config.timeout {
+                let _ = options
+                    .elasticsearch_subsystem_timeouts
+                    .insert(subsystem, timeout);
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Opts;
+    use clap::Clap;
+    use elasticsearch_exporter::credentials::Secret;
+
+    fn default_options() -> ExporterOptions {
+        Opts::parse_from(vec!["elasticsearch_exporter"]).exporter_options()
+    }
+
+    #[test]
+    fn test_parse_error_names_key() {
+        let err = deserialize::<Config>(
+            Path::new("config.yml"),
+            "subsystems:\n  cat_indices:\n    interval: often\n",
+        )
+        .unwrap_err();
+        assert!(
+            err.starts_with("subsystems.cat_indices.interval"),
+            "{}",
+            err
+        );
+
+        let err = deserialize::<Config>(
+            Path::new("config.toml"),
+            "[subsystems.cat_indices]\nincude_labels = [\"index\"]\n",
+        )
+        .unwrap_err();
+        assert!(err.starts_with("subsystems.cat_indices"), "{}", err);
+        assert!(err.contains("incude_labels"), "{}", err);
+    }
+
+    #[test]
+    fn test_unknown_subsystem() {
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "subsystems:\n  cat_indicez:\n    enabled: true\n",
+        )
+        .unwrap();
+
+        let mut options = default_options();
+        let err = config.apply(&mut options, |_| false).unwrap_err();
+        assert_eq!(err.to_string(), "subsystems.cat_indicez: unknown subsystem");
+    }
+
+    #[test]
+    fn test_queries() {
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "exporter_queries:\n  - name: errors\n    index: logs-*\n    interval: 1m\n    query:\n      term: { level: error }\n",
+        )
+        .unwrap();
+
+        let mut options = default_options();
+        config.apply(&mut options, |_| false).unwrap();
+
+        assert_eq!(options.exporter_queries.len(), 1);
+        assert_eq!(options.exporter_queries[0].index, "logs-*");
+        assert_eq!(
+            options.exporter_queries[0].interval,
+            Some(Duration::from_secs(60))
+        );
+
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "exporter_queries:\n  - name: errors\n    index: logs-*\n  - name: errors\n    index: metrics-*\n",
+        )
+        .unwrap();
+
+        let err = config.apply(&mut default_options(), |_| false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "exporter_queries.errors: duplicate query name"
+        );
+    }
+
+    #[test]
+    fn test_explicit_flags_override_file() {
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "elasticsearch_url: http://es-file:9200\nsubsystems:\n  cat_indices:\n    interval: 30s\n    include_labels: [index]\n  cat_nodes:\n    include_labels: [name]\n",
+        )
+        .unwrap();
+
+        let mut options = default_options();
+        let _ = options
+            .exporter_include_labels
+            .insert("cat_nodes".into(), vec!["ip".into()]);
+
+        config
+            .apply(&mut options, |flag| flag == "exporter_include_labels")
+            .unwrap();
+
+        assert_eq!(options.elasticsearch_url.as_str(), "http://es-file:9200/");
+        assert_eq!(
+            options.exporter_poll_intervals.get("cat_indices"),
+            Some(&Duration::from_secs(30))
+        );
+        assert_eq!(
+            options.exporter_include_labels.get("cat_indices"),
+            Some(&vec!["index".to_string()])
+        );
+        // Explicit flag value wins
+        assert_eq!(
+            options.exporter_include_labels.get("cat_nodes"),
+            Some(&vec!["ip".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_reload_diff() {
+        let current = default_options();
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "subsystems:\n  cat_indices:\n    include_labels: [index, health]\n  cluster_health:\n    interval: 5s\n",
+        )
+        .unwrap();
+
+        let mut reloaded = default_options();
+        config.apply(&mut reloaded, |_| false).unwrap();
+
+        // cluster_health interval is the same as default flag value
+        assert_eq!(current.changed_subsystems(&reloaded), vec!["cat_indices"]);
+        assert!(current.restart_required(&reloaded).is_empty());
+
+        reloaded.elasticsearch_url = Url::parse("http://es-2:9200").unwrap();
+        assert_eq!(
+            current.restart_required(&reloaded),
+            vec!["elasticsearch_url"]
+        );
+    }
+
+    #[test]
+    fn test_reload_diff_subsystem_options() {
+        let current = default_options();
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "exporter_queries:\n  - name: errors\n    index: logs-*\nexporter_canary_retention: 2h\nexporter_cluster_settings: [cluster.routing.allocation.enable]\nexporter_tasks_long_running_threshold: 1m\nexporter_allocation_explain_max_shards: 5\nexporter_index_freshness_window: 10m\nexporter_nodes_stats_ingest_processors: true\n",
+        )
+        .unwrap();
+
+        let mut reloaded = default_options();
+        config.apply(&mut reloaded, |_| false).unwrap();
+
+        assert_eq!(
+            current.changed_subsystems(&reloaded),
+            vec![
+                "allocation_explain",
+                "cluster_settings",
+                "canary",
+                "nodes_stats",
+                "index_freshness",
+                "queries",
+                "tasks",
+            ]
+        );
+        assert!(current.restart_required(&reloaded).is_empty());
+    }
+
+    #[test]
+    fn test_clusters() {
+        let config = deserialize::<Config>(
+            Path::new("config.yml"),
+            "elasticsearch_auth:\n  bearer: { value: global }\nelasticsearch_tls:\n  ca_cert: /certs/ca.pem\nelasticsearch_clusters:\n  - url: https://es-a:9200\n    name: es-a\n    auth:\n      api_key: { file: /secrets/es-a }\n    tls:\n      insecure: true\n    subsystems:\n      cat_health:\n        interval: 5s\n  - url: https://es-b:9200\n",
+        )
+        .unwrap();
+
+        let mut options = default_options();
+        config.apply(&mut options, |_| false).unwrap();
+
+        let clusters = options.clusters();
+        assert_eq!(clusters.len(), 2);
+
+        let (a, b) = (&clusters[0], &clusters[1]);
+
+        assert_eq!(a.elasticsearch_url.as_str(), "https://es-a:9200/");
+        assert_eq!(a.elasticsearch_cluster_name.as_deref(), Some("es-a"));
+        assert!(a.elasticsearch_clusters.is_empty());
+        assert_eq!(
+            a.elasticsearch_auth,
+            Some(Auth::ApiKey(Secret::File("/secrets/es-a".into())))
+        );
+        assert_eq!(
+            a.elasticsearch_tls,
+            Tls {
+                insecure: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            a.exporter_poll_intervals.get("cat_health"),
+            Some(&Duration::from_secs(5))
+        );
+
+        // Cluster without own credentials and TLS falls back to exporter ones
+        assert_eq!(b.elasticsearch_url.as_str(), "https://es-b:9200/");
+        assert_eq!(b.elasticsearch_cluster_name, None);
+        assert_eq!(
+            b.elasticsearch_auth,
+            Some(Auth::Bearer(Secret::Value("global".into())))
+        );
+        assert_eq!(
+            b.elasticsearch_tls.ca_cert.as_deref(),
+            Some(Path::new("/certs/ca.pem"))
+        );
+        assert_eq!(b.exporter_poll_intervals.get("cat_health"), None);
+    }
+}
@@ -5,17 +5,23 @@ extern crate prometheus;
 #[macro_use]
 extern crate log;
 
-use clap::Clap;
+use clap::{ArgMatches, FromArgMatches, IntoApp};
 use hyper::{
-    header::CONTENT_TYPE,
+    header::{CONTENT_TYPE, WWW_AUTHENTICATE},
     server::conn::AddrStream,
     service::{make_service_fn, service_fn},
-    Body, Request, Response, Server, StatusCode,
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use prometheus::{
+    proto::MetricFamily, Encoder, HistogramVec, IntCounterVec, IntGauge, TextEncoder, TEXT_FORMAT,
 };
-use prometheus::{Encoder, HistogramVec, TextEncoder, TEXT_FORMAT};
 use std::convert::Infallible;
 use std::env;
+use std::fs;
 use std::panic;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use url::Url;
 
 use elasticsearch_exporter::{Exporter, ExporterOptions};
 
@@ -26,6 +32,17 @@ lazy_static! {
         &["handler"]
     )
     .expect("valid histogram vec metric");
+    static ref CONFIG_LAST_RELOAD_SUCCESSFUL: IntGauge = register_int_gauge!(
+        "elasticsearch_exporter_config_last_reload_successful",
+        "Whether the last configuration reload attempt was successful."
+    )
+    .expect("valid int gauge metric");
+    static ref CONFIG_RELOADS: IntCounterVec = register_int_counter_vec!(
+        "elasticsearch_exporter_config_reloads_total",
+        "Configuration reload attempts by result.",
+        &["result"]
+    )
+    .expect("valid int counter vec metric");
 }
 
 fn build_response(status: StatusCode, body: Body) -> Response<Body> {
@@ -36,28 +53,286 @@ fn build_response(status: StatusCode, body: Body) -> Response<Body> {
         .expect("valid Response built")
 }
 
-async fn serve_req(req: Request<Body>, options: String) -> Result<Response<Body>, Infallible> {
+/// State shared between HTTP requests
+struct Context {
+    /// Exporter options used as a base for /probe targets, replaced on reload
+    exporter_options: RwLock<ExporterOptions>,
+    /// Exporter per cluster in order of cluster options, empty until cluster is connected
+    exporters: RwLock<Vec<Option<Exporter>>>,
+    /// Serve /subsystems admin endpoint
+    admin_enabled: bool,
+    /// Serve /probe endpoint
+    probe_enabled: bool,
+    /// Listener TLS and basic authentication, replaced on reload
+    web_config: RwLock<Arc<WebConfig>>,
+    /// Listener TLS acceptor, rebuilt on reload
+    tls_reloader: Option<TlsReloader>,
+}
+
+impl Context {
+    /// Exporters of connected clusters
+    fn exporters(&self) -> Vec<Exporter> {
+        self.exporters
+            .read()
+            .expect("exporters lock")
+            .iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+fn encode_response(families: &[MetricFamily]) -> Response<Body> {
+    let encoder = TextEncoder::new();
+
+    let mut buffer = vec![];
+    match encoder.encode(families, &mut buffer) {
+        Ok(_) => build_response(StatusCode::OK, Body::from(buffer)),
+        Err(e) => {
+            error!("prometheus encoder err {}", e);
+
+            build_response(StatusCode::INTERNAL_SERVER_ERROR, Body::empty())
+        }
+    }
+}
+
+// GET /probe?target=<url>&module=<subsystem>[,<subsystem>]
+//
+// Subsystems enabled in exporter options are probed if module is not provided
+async fn serve_probe(req: &Request<Body>, options: &ExporterOptions) -> Response<Body> {
+    let mut target = None;
+    let mut modules = Vec::new();
+
+    if let Some(query) = req.uri().query() {
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "target" => target = Some(value.into_owned()),
+                "module" => modules.extend(
+                    value
+                        .split(',')
+                        .filter(|module| !module.is_empty())
+                        .map(String::from),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let target = match target.as_deref().map(Url::parse) {
+        Some(Ok(url)) if url.scheme() == "http" || url.scheme() == "https" => url,
+        Some(Ok(url)) => {
+            return build_response(
+                StatusCode::BAD_REQUEST,
+                Body::from(format!("Target scheme {} is not supported", url.scheme())),
+            )
+        }
+        Some(Err(e)) => {
+            return build_response(
+                StatusCode::BAD_REQUEST,
+                Body::from(format!("Failed to parse target err {}", e)),
+            )
+        }
+        None => {
+            return build_response(
+                StatusCode::BAD_REQUEST,
+                Body::from("Target parameter is missing"),
+            )
+        }
+    };
+
+    let subsystems = ExporterOptions::subsystems();
+    let excluded = ExporterOptions::probe_excluded_subsystems();
+
+    if modules.is_empty() {
+        modules = subsystems
+            .iter()
+            .filter(|subsystem| options.is_metric_enabled(subsystem))
+            .filter(|subsystem| !excluded.contains(subsystem))
+            .map(|subsystem| subsystem.to_string())
+            .collect();
+    }
+
+    if let Some(module) = modules
+        .iter()
+        .find(|module| !subsystems.contains(&module.as_str()))
+    {
+        return build_response(
+            StatusCode::BAD_REQUEST,
+            Body::from(format!("Unknown module {}", module)),
+        );
+    }
+
+    if let Some(module) = modules
+        .iter()
+        .find(|module| excluded.contains(&module.as_str()))
+    {
+        return build_response(
+            StatusCode::BAD_REQUEST,
+            Body::from(format!("Module {} can't be probed", module)),
+        );
+    }
+
+    let modules = modules.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    match Exporter::probe(options, target.clone(), &modules).await {
+        Ok(families) => encode_response(&families),
+        Err(e) => {
+            error!(
+                "probe {} err {}",
+                elasticsearch_exporter::credentials::redact_url(&target),
+                e
+            );
+
+            build_response(
+                StatusCode::BAD_GATEWAY,
+                Body::from(format!("Probe failed err {}", e)),
+            )
+        }
+    }
+}
+
+// GET /subsystems lists running subsystems
+// POST /subsystems/<subsystem>/start starts subsystem poller
+// POST /subsystems/<subsystem>/stop stops subsystem poller and removes its metrics
+//
+// Optional query parameter ?cluster=<cluster> narrows down exporters to given cluster
+async fn serve_admin(req: &Request<Body>, exporters: &[Exporter]) -> Response<Body> {
     let path = req.uri().path();
+    let parts = path.trim_matches('/').split('/').collect::<Vec<&str>>();
 
-    let timer = HTTP_REQ_HISTOGRAM.with_label_values(&[path]).start_timer();
+    let cluster = req.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "cluster")
+            .map(|(_, value)| value.into_owned())
+    });
 
-    let response = match path {
-        "/health" | "/healthy" | "/healthz" => build_response(StatusCode::OK, Body::from("Ok")),
-        "/" => build_response(StatusCode::OK, Body::from(options)),
+    let exporters = exporters
+        .iter()
+        .filter(|exporter| {
+            cluster
+                .as_ref()
+                .map(|cluster| cluster == exporter.cluster_name())
+                .unwrap_or(true)
+        })
+        .collect::<Vec<&Exporter>>();
+
+    if exporters.is_empty() {
+        return build_response(
+            StatusCode::NOT_FOUND,
+            Body::from(format!("Cluster {:?} not found", cluster)),
+        );
+    }
+
+    match (req.method(), parts.as_slice()) {
+        (&Method::GET, ["subsystems"]) => {
+            let mut output = String::new();
+
+            for exporter in exporters.iter() {
+                output.push_str(&format!("{}:\n", exporter.cluster_name()));
+                for subsystem in exporter.running_subsystems() {
+                    output.push_str(&format!(" - {}\n", subsystem));
+                }
+            }
+
+            build_response(StatusCode::OK, Body::from(output))
+        }
+        (&Method::POST, ["subsystems", subsystem, action]) => {
+            if !ExporterOptions::subsystems()
+                .iter()
+                .any(|available| available == subsystem)
+            {
+                return build_response(
+                    StatusCode::NOT_FOUND,
+                    Body::from(format!("Subsystem {} not found", subsystem)),
+                );
+            }
 
-        "/metrics" => {
-            let encoder = TextEncoder::new();
+            let state = match *action {
+                "start" => "running",
+                "stop" => "stopped",
+                _ => {
+                    return build_response(
+                        StatusCode::NOT_FOUND,
+                        Body::from(format!("Path {} not found", path)),
+                    )
+                }
+            };
 
-            let mut buffer = vec![];
-            match encoder.encode(&prometheus::gather(), &mut buffer) {
-                Ok(_) => build_response(StatusCode::OK, Body::from(buffer)),
-                Err(e) => {
-                    error!("prometheus encoder err {}", e);
+            let mut changed = false;
 
-                    build_response(StatusCode::INTERNAL_SERVER_ERROR, Body::empty())
+            for exporter in exporters.iter() {
+                let exporter_changed = match *action {
+                    "start" => exporter.start_subsystem(subsystem),
+                    _ => exporter.stop_subsystem(subsystem).await,
+                };
+
+                if exporter_changed {
+                    info!(
+                        "Admin: cluster {} subsystem {} is {}",
+                        exporter.cluster_name(),
+                        subsystem,
+                        state
+                    );
                 }
+
+                changed |= exporter_changed;
+            }
+
+            if changed {
+                build_response(StatusCode::OK, Body::from("Ok"))
+            } else {
+                build_response(
+                    StatusCode::CONFLICT,
+                    Body::from(format!("Subsystem {} is already {}", subsystem, state)),
+                )
             }
         }
+        _ => build_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            Body::from(format!("Method {} not allowed for {}", req.method(), path)),
+        ),
+    }
+}
+
+async fn serve_req(req: Request<Body>, ctx: Arc<Context>) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path();
+
+    let timer = HTTP_REQ_HISTOGRAM.with_label_values(&[path]).start_timer();
+
+    let web_config = ctx.web_config.read().expect("web config lock").clone();
+
+    if !web_config.authorized(&req).await {
+        timer.observe_duration();
+
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .header(WWW_AUTHENTICATE, "Basic")
+            .body(Body::from("Unauthorized"))
+            .expect("valid Response built"));
+    }
+
+    let response = match path {
+        "/health" | "/healthy" | "/healthz" => build_response(StatusCode::OK, Body::from("Ok")),
+        "/" => build_response(
+            StatusCode::OK,
+            Body::from(
+                ctx.exporter_options
+                    .read()
+                    .expect("options lock")
+                    .to_string(),
+            ),
+        ),
+        path if ctx.admin_enabled && path.starts_with("/subsystems") => {
+            serve_admin(&req, &ctx.exporters()).await
+        }
+
+        "/probe" if ctx.probe_enabled => {
+            let options = ctx.exporter_options.read().expect("options lock").clone();
+
+            serve_probe(&req, &options).await
+        }
+
+        "/metrics" => encode_response(&elasticsearch_exporter::gather(&ctx.exporters())),
         _ => build_response(
             StatusCode::NOT_FOUND,
             Body::from(format!("Path {} not found", path)),
@@ -78,7 +353,238 @@ pub fn panic_hook() {
 }
 
 mod cli;
-use cli::{signal_channel, Opts};
+use cli::{reload_channel, signal_channel, Opts};
+
+mod web;
+use web::{TlsIncoming, TlsReloader, WebConfig};
+
+mod config;
+use config::Config;
+
+/// Exporter options from flags and configuration file, flags
+/// provided explicitly take precedence over configuration file
+fn exporter_options(
+    opts: &Opts,
+    matches: &ArgMatches,
+) -> Result<ExporterOptions, Box<dyn std::error::Error>> {
+    let mut options = opts.exporter_options();
+
+    if let Some(ref path) = opts.config {
+        Config::load(path)?.apply(&mut options, |flag| {
+            matches.occurrences_of(flag.replace('_', "-").as_str()) > 0
+        })?;
+    }
+
+    Ok(options)
+}
+
+/// Re-read options and apply them to running exporters, only pollers
+/// of subsystems with changed options are restarted. Web configuration
+/// and listener certificates are re-read as well
+async fn reload(
+    ctx: &Context,
+    opts: &Opts,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let options = exporter_options(opts, matches)?;
+
+    let web_config = match opts.web_config_file {
+        Some(ref path) => WebConfig::load(path)?,
+        None => WebConfig::default(),
+    };
+
+    // Listener is bound on start, TLS can't be switched on or off
+    let server_config = match (&ctx.tls_reloader, &web_config.tls_server_config) {
+        (Some(_), Some(tls)) => Some(tls.server_config()?),
+        (None, None) => None,
+        _ => return Err("tls_server_config change requires restart".into()),
+    };
+
+    let restart_required = ctx
+        .exporter_options
+        .read()
+        .expect("options lock")
+        .restart_required(&options);
+
+    if !restart_required.is_empty() {
+        return Err(format!("{} change requires restart", restart_required.join(", ")).into());
+    }
+
+    // Clusters are unchanged, exporters are in the same order as cluster options,
+    // clusters not connected yet pick up reloaded options on the next attempt
+    let exporters = ctx
+        .exporters
+        .read()
+        .expect("exporters lock")
+        .iter()
+        .zip(options.clusters())
+        .filter_map(|(exporter, cluster_options)| {
+            exporter
+                .as_ref()
+                .map(|exporter| (exporter.clone(), cluster_options))
+        })
+        .collect::<Vec<(Exporter, ExporterOptions)>>();
+
+    // Every exporter is validated before any is reloaded,
+    // so failed reload does not leave clusters with mixed options
+    for (exporter, cluster_options) in exporters.iter() {
+        let restart_required = exporter.options().restart_required(cluster_options);
+
+        if !restart_required.is_empty() {
+            return Err(format!(
+                "cluster {} {} change requires restart",
+                exporter.cluster_name(),
+                restart_required.join(", ")
+            )
+            .into());
+        }
+    }
+
+    for (exporter, cluster_options) in exporters {
+        let restarted = exporter.reload(cluster_options).await?;
+
+        if !restarted.is_empty() {
+            info!(
+                "Reload: cluster {} restarted subsystems: {}",
+                exporter.cluster_name(),
+                restarted.join(", ")
+            );
+        }
+    }
+
+    info!("{}", options);
+    *ctx.exporter_options.write().expect("options lock") = options;
+
+    if let (Some(reloader), Some(server_config)) = (&ctx.tls_reloader, server_config) {
+        reloader.reload(server_config);
+    }
+    *ctx.web_config.write().expect("web config lock") = Arc::new(web_config);
+
+    Ok(())
+}
+
+/// Modification times of configuration, web configuration and listener certificate files
+fn config_modified(ctx: &Context, opts: &Opts) -> Vec<Option<SystemTime>> {
+    opts.config
+        .iter()
+        .chain(opts.web_config_file.iter())
+        .cloned()
+        .chain(ctx.web_config.read().expect("web config lock").files())
+        .map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+/// Reload options on SIGHUP or on configuration file modification
+async fn watch_reload(ctx: Arc<Context>, opts: Opts, matches: ArgMatches) {
+    let mut reload_rx = reload_channel();
+    let mut modified = config_modified(&ctx, &opts);
+    let mut watch = opts
+        .config_watch_interval
+        .map(|interval| tokio::time::interval(*interval));
+
+    CONFIG_LAST_RELOAD_SUCCESSFUL.set(1);
+
+    loop {
+        let watch_tick = async {
+            match watch {
+                Some(ref mut watch) => {
+                    let _ = watch.tick().await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            Some(_) = reload_rx.recv() => {},
+            _ = watch_tick => {
+                if config_modified(&ctx, &opts) == modified {
+                    continue;
+                }
+
+                info!("Configuration file changed: reloading configuration");
+            },
+        }
+
+        let result = reload(&ctx, &opts, &matches).await;
+
+        // Files modified before SIGHUP must not trigger another reload,
+        // reloaded web configuration may point to other certificate files
+        modified = config_modified(&ctx, &opts);
+
+        match result {
+            Ok(_) => {
+                CONFIG_LAST_RELOAD_SUCCESSFUL.set(1);
+                CONFIG_RELOADS.with_label_values(&["success"]).inc();
+            }
+            Err(e) => {
+                error!("reload configuration err {}", e);
+                CONFIG_LAST_RELOAD_SUCCESSFUL.set(0);
+                CONFIG_RELOADS.with_label_values(&["failure"]).inc();
+            }
+        }
+    }
+}
+
+/// Connect exporter of cluster at given index and spawn its collectors, failed
+/// connection is retried every pool check interval, so single unreachable
+/// cluster does not stop monitoring of the others
+async fn connect(ctx: Arc<Context>, index: usize) {
+    loop {
+        // Options are read on every attempt to pick up reloaded options
+        let cluster_options = ctx
+            .exporter_options
+            .read()
+            .expect("options lock")
+            .clusters()
+            .into_iter()
+            .nth(index);
+
+        let cluster_options = match cluster_options {
+            Some(cluster_options) => cluster_options,
+            None => return,
+        };
+
+        let url =
+            elasticsearch_exporter::credentials::redact_url(&cluster_options.elasticsearch_url);
+        let retry_interval = cluster_options.elasticsearch_pool_check_interval;
+
+        match Exporter::new(cluster_options).await {
+            Ok(exporter) => {
+                info!("Cluster {} connected", exporter.cluster_name());
+
+                let _ = tokio::spawn(exporter.clone().spawn());
+                ctx.exporters.write().expect("exporters lock")[index] = Some(exporter);
+
+                return;
+            }
+            Err(e) => error!(
+                "connect cluster {} err {}, retrying in {:?}",
+                url, e, retry_interval
+            ),
+        }
+
+        tokio::time::sleep(retry_interval).await;
+    }
+}
+
+/// Apply HTTP settings shared by plain and TLS listeners
+macro_rules! configure_http {
+    ($builder:expr, $opts:expr) => {
+        $builder
+            // HTTP 1
+            .http1_keepalive(true)
+            .http1_half_close(false)
+            .http1_max_buf_size($opts.hyper_http1_max_buf_size)
+            // HTTP 2
+            .http2_keep_alive_interval(*$opts.hyper_tcp_keepalive)
+            .http2_keep_alive_timeout(*$opts.hyper_http2_keep_alive_timeout)
+            .http2_adaptive_window(true)
+    };
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -89,77 +595,504 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     pretty_env_logger::init();
-    let mut opts = Opts::parse();
+    let matches = Opts::into_app().get_matches();
+    let mut opts = Opts::from_arg_matches(&matches).expect("parsed flags");
 
     if let Ok(Ok(port)) = env::var("PORT").map(|p| p.parse::<u16>()) {
         opts.listen_addr.set_port(port);
     }
 
-    let options = ExporterOptions {
-        elasticsearch_url: opts.elasticsearch_url.clone(),
-        elasticsearch_global_timeout: *opts.elasticsearch_global_timeout,
-        elasticsearch_query_fields: opts.elasticsearch_query_fields.0.clone(),
-        elasticsearch_subsystem_timeouts: opts.elasticsearch_subsystem_timeouts.0.clone(),
-        elasticsearch_path_parameters: opts.elasticsearch_path_parameters.0.clone(),
-
-        exporter_skip_labels: opts.exporter_skip_labels.0.clone(),
-        exporter_skip_metrics: opts.exporter_skip_metrics.0.clone(),
-        exporter_include_labels: opts.exporter_include_labels.0.clone(),
-        exporter_poll_default_interval: *opts.exporter_poll_default_interval,
-        exporter_skip_zero_metrics: !opts.exporter_allow_zero_metrics,
-        exporter_poll_intervals: opts.exporter_poll_intervals.0.clone(),
-        exporter_metrics_enabled: opts.exporter_metrics_enabled.0.clone(),
-        exporter_metadata_refresh_interval: *opts.exporter_metadata_refresh_interval,
-
-        exporter_metrics_lifetime_interval: opts.exporter_metrics_lifetime_interval.0.clone(),
-        exporter_metrics_lifetime_default_interval: *opts
-            .exporter_metrics_lifetime_default_interval,
+    let options = match exporter_options(&opts, &matches) {
+        Ok(options) => options,
+        Err(e) => {
+            error!("{}", e);
+
+            std::process::exit(78);
+        }
     };
 
     info!("{}", options);
 
-    let options_clone = options.clone();
-    let new_service = make_service_fn(move |socket: &AddrStream| {
-        let options_string = options_clone.to_string();
+    let web_config = match opts.web_config_file {
+        Some(ref path) => match WebConfig::load(path) {
+            Ok(web_config) => web_config,
+            Err(e) => {
+                error!("{}", e);
 
-        let svc = service_fn(move |req| serve_req(req, options_string.clone()));
-        trace!("incoming socket request: {:?}", socket);
-        async move { Ok::<_, Infallible>(svc) }
-    });
+                std::process::exit(78);
+            }
+        },
+        None => WebConfig::default(),
+    };
+
+    let tls_reloader = match web_config.tls_server_config {
+        Some(ref tls) => match TlsReloader::new(tls) {
+            Ok(reloader) => Some(reloader),
+            Err(e) => {
+                error!("{}", e);
+
+                std::process::exit(78);
+            }
+        },
+        None => None,
+    };
 
     let signal_rx = signal_channel();
 
-    match Exporter::new(options).await {
-        Ok(exporter) => {
-            let _ = tokio::spawn(exporter.spawn());
-        }
-        Err(e) => {
-            error!("{}", e);
+    let clusters = options.clusters().len();
 
-            std::process::exit(70);
-        }
+    let ctx = Arc::new(Context {
+        exporter_options: RwLock::new(options),
+        exporters: RwLock::new(vec![None; clusters]),
+        admin_enabled: opts.exporter_admin_enabled,
+        probe_enabled: opts.exporter_probe_enabled,
+        web_config: RwLock::new(Arc::new(web_config)),
+        tls_reloader,
+    });
+
+    for index in 0..clusters {
+        let _ = tokio::spawn(connect(ctx.clone(), index));
     }
 
+    let _ = tokio::spawn(watch_reload(ctx.clone(), opts.clone(), matches));
+
+    let shutdown = async move {
+        signal_rx.await.ok();
+        info!("Graceful context shutdown");
+    };
+
+    if let Some(ref reloader) = ctx.tls_reloader {
+        let incoming = TlsIncoming::bind(opts.listen_addr, reloader.clone()).await?;
+
+        let ctx = ctx.clone();
+        let new_service = make_service_fn(move |_| {
+            let ctx = ctx.clone();
+
+            let svc = service_fn(move |req| serve_req(req, ctx.clone()));
+            async move { Ok::<_, Infallible>(svc) }
+        });
+
+        info!("Listening on https://{}", opts.listen_addr);
+
+        configure_http!(Server::builder(incoming), opts)
+            .serve(new_service)
+            .with_graceful_shutdown(shutdown)
+            .await?;
+
+        return Ok(());
+    }
+
+    let new_service = make_service_fn(move |socket: &AddrStream| {
+        let ctx = ctx.clone();
+
+        let svc = service_fn(move |req| serve_req(req, ctx.clone()));
+        trace!("incoming socket request: {:?}", socket);
+        async move { Ok::<_, Infallible>(svc) }
+    });
+
     info!("Listening on http://{}", opts.listen_addr);
 
-    Server::bind(&opts.listen_addr)
+    let builder = Server::bind(&opts.listen_addr)
         // TCP
         .tcp_keepalive(Some(*opts.hyper_tcp_keepalive))
-        .tcp_nodelay(true)
-        // HTTP 1
-        .http1_keepalive(true)
-        .http1_half_close(false)
-        .http1_max_buf_size(opts.hyper_http1_max_buf_size)
-        // HTTP 2
-        .http2_keep_alive_interval(*opts.hyper_tcp_keepalive)
-        .http2_keep_alive_timeout(*opts.hyper_http2_keep_alive_timeout)
-        .http2_adaptive_window(true)
+        .tcp_nodelay(true);
+
+    configure_http!(builder, opts)
         .serve(new_service)
-        .with_graceful_shutdown(async move {
-            signal_rx.await.ok();
-            info!("Graceful context shutdown");
-        })
+        .with_graceful_shutdown(shutdown)
         .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Clap;
+    use prometheus::Opts as MetricOpts;
+
+    type Authorizations = Arc<std::sync::Mutex<Vec<Option<String>>>>;
+
+    /// Local stand-in of Elasticsearch answering every request with cluster name,
+    /// authorization header of each request is recorded
+    async fn stand_in_recording() -> (Url, Authorizations) {
+        let authorizations = Authorizations::default();
+        let recorded = authorizations.clone();
+
+        let service = make_service_fn(move |_| {
+            let recorded = recorded.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    recorded.lock().expect("authorizations lock").push(
+                        req.headers()
+                            .get(hyper::header::AUTHORIZATION)
+                            .and_then(|value| value.to_str().ok())
+                            .map(String::from),
+                    );
+
+                    async {
+                        Ok::<_, Infallible>(
+                            Response::builder()
+                                .header(CONTENT_TYPE, "application/json")
+                                .body(Body::from(r#"{"cluster_name":"stand-in"}"#))
+                                .expect("valid response"),
+                        )
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+        let url = Url::parse(&format!("http://{}", server.local_addr())).expect("valid url");
+        let _ = tokio::spawn(server);
+
+        (url, authorizations)
+    }
+
+    async fn stand_in() -> Url {
+        stand_in_recording().await.0
+    }
+
+    /// Local stand-in of Elasticsearch answering requests by path
+    async fn stand_in_routes(route: fn(&str) -> (u16, &'static str)) -> Url {
+        let service = make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| async move {
+                let (status, body) = route(req.uri().path());
+
+                Ok::<_, Infallible>(
+                    Response::builder()
+                        .status(status)
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(Body::from(body))
+                        .expect("valid response"),
+                )
+            }))
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+        let url = Url::parse(&format!("http://{}", server.local_addr())).expect("valid url");
+        let _ = tokio::spawn(server);
+
+        url
+    }
+
+    async fn probe(options: &ExporterOptions, target: &str) -> (StatusCode, String) {
+        probe_module(options, target, "cluster_health").await
+    }
+
+    async fn probe_module(
+        options: &ExporterOptions,
+        target: &str,
+        module: &str,
+    ) -> (StatusCode, String) {
+        let req = Request::get(format!("/probe?target={}&module={}", target, module))
+            .body(Body::empty())
+            .expect("valid request");
+
+        let response = serve_probe(&req, options).await;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .expect("response body");
+
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn exporter_options(args: &[&str]) -> ExporterOptions {
+        let mut options =
+            Opts::parse_from([&["elasticsearch_exporter"], args].concat()).exporter_options();
+        // No subsystems, so exporters do not request node metadata
+        options.exporter_metrics_enabled = Default::default();
+        options
+    }
+
+    #[tokio::test]
+    async fn test_gather() {
+        let url = stand_in().await;
+        let mut exporters = Vec::new();
+
+        for name in &["es-a", "es-b"] {
+            let mut options = exporter_options(&[]);
+            options.elasticsearch_url = url.clone();
+            options.elasticsearch_cluster_name = Some(name.to_string());
+
+            let exporter = Exporter::new(options).await.expect("exporter");
+
+            let gauge = IntGauge::with_opts(
+                MetricOpts::new("test_gather_up", "Test gauge")
+                    .const_labels(exporter.const_labels()),
+            )
+            .expect("valid gauge");
+            gauge.set(1);
+            exporter
+                .registry()
+                .register(Box::new(gauge))
+                .expect("registered gauge");
+
+            exporters.push(exporter);
+        }
+
+        lazy_static::initialize(&CONFIG_LAST_RELOAD_SUCCESSFUL);
+
+        let families = elasticsearch_exporter::gather(&exporters);
+
+        // Families of the same name are merged across cluster registries
+        let family = families
+            .iter()
+            .find(|family| family.get_name() == "test_gather_up")
+            .expect("merged family");
+        let clusters = family
+            .get_metric()
+            .iter()
+            .map(|metric| metric.get_label()[0].get_value())
+            .collect::<Vec<&str>>();
+        assert_eq!(clusters, vec!["es-a", "es-b"]);
+
+        // Default registry is gathered too
+        assert!(families
+            .iter()
+            .any(|family| family.get_name()
+                == "elasticsearch_exporter_config_last_reload_successful"));
+    }
+
+    #[tokio::test]
+    async fn test_sniff_failure_keeps_seeds() {
+        // Node discovery is answered with invalid body
+        let url = stand_in_routes(|path| {
+            if path.starts_with("/_nodes") {
+                (200, "garbage")
+            } else {
+                (200, r#"{"cluster_name":"stand-in"}"#)
+            }
+        })
+        .await;
+
+        let exporter = Exporter::new(exporter_options(&[
+            &format!("--elasticsearch_url={}", url),
+            "--elasticsearch_sniff",
+        ]))
+        .await
+        .expect("exporter of seed nodes");
+
+        assert_eq!(exporter.cluster_name(), "stand-in");
+    }
+
+    #[tokio::test]
+    async fn test_index_freshness_failed_pattern() {
+        let url = stand_in_routes(|path| {
+            if path.contains("broken") {
+                (
+                    400,
+                    r#"{"error":{"type":"search_phase_execution_exception"},"status":400}"#,
+                )
+            } else if path.ends_with("/_search") {
+                (200, include_str!("../tests/files/index_freshness.json"))
+            } else {
+                (200, r#"{"cluster_name":"stand-in"}"#)
+            }
+        })
+        .await;
+
+        let options =
+            exporter_options(&["--elasticsearch_path_parameters=index_freshness=broken-*,logs-*"]);
+
+        let (status, body) = probe_module(&options, url.as_str(), "index_freshness").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("pattern=\"logs-*\""), "{}", body);
+        assert!(!body.contains("pattern=\"broken-*\""), "{}", body);
+        assert!(
+            body.contains("probe_success{cluster=\"stand-in\",subsystem=\"index_freshness\"} 1"),
+            "{}",
+            body
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queries_failed_query() {
+        let url = stand_in_routes(|path| {
+            if path.contains("broken") {
+                (
+                    400,
+                    r#"{"error":{"type":"search_phase_execution_exception"},"status":400}"#,
+                )
+            } else if path.ends_with("/_search") {
+                (200, include_str!("../tests/files/queries.json"))
+            } else {
+                (200, r#"{"cluster_name":"stand-in"}"#)
+            }
+        })
+        .await;
+
+        let mut options = exporter_options(&[]);
+        for (name, index) in &[("broken", "broken-*"), ("errors", "logs-*")] {
+            options.exporter_queries.push(
+                serde_json::from_value(serde_json::json!({ "name": name, "index": index }))
+                    .expect("valid query"),
+            );
+        }
+
+        // Queries are not probed, they run from pollers only
+        let (status, _) = probe_module(&options, url.as_str(), "queries").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        options.elasticsearch_url = url;
+        let exporter = Exporter::new(options).await.expect("exporter");
+        assert!(exporter.start_subsystem("queries"));
+
+        let mut body = String::new();
+        for _ in 0..50 {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+            let mut buffer = Vec::new();
+            TextEncoder::new()
+                .encode(&exporter.registry().gather(), &mut buffer)
+                .expect("encoded metrics");
+            body = String::from_utf8_lossy(&buffer).into_owned();
+
+            if body.contains("query=\"errors\"") {
+                break;
+            }
+        }
+
+        assert!(body.contains("query=\"errors\""), "{}", body);
+        assert!(!body.contains("query=\"broken\""), "{}", body);
+        let _ = exporter.stop_subsystem("queries").await;
+    }
+
+    #[tokio::test]
+    async fn test_cluster_stats_architectures() {
+        let url = stand_in_routes(|path| {
+            if path.starts_with("/_cluster/stats") {
+                (
+                    200,
+                    r#"{"nodes":{"os":{"architectures":[{"arch":"amd64","count":3},{"arch":"aarch64","count":2}]}}}"#,
+                )
+            } else {
+                (200, r#"{"cluster_name":"stand-in"}"#)
+            }
+        })
+        .await;
+
+        let options = exporter_options(&[]);
+
+        let (status, body) = probe_module(&options, url.as_str(), "cluster_stats").await;
+        assert_eq!(status, StatusCode::OK);
+        // Each architecture is exported as its own series
+        assert!(body.contains("arch=\"amd64\""), "{}", body);
+        assert!(body.contains("arch=\"aarch64\""), "{}", body);
+        assert_eq!(
+            body.lines()
+                .filter(|line| line
+                    .starts_with("elasticsearch_cluster_stats_nodes_os_architectures_count{"))
+                .count(),
+            2,
+            "{}",
+            body
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_excluded_module() {
+        let options = exporter_options(&[]);
+
+        for module in &["canary", "queries", "cluster_health,canary"] {
+            let (status, body) = probe_module(&options, "http://127.0.0.1:9", module).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body.contains("can't be probed"), "{}", body);
+        }
+
+        let result = Exporter::probe(
+            &options,
+            Url::parse("http://127.0.0.1:9").unwrap(),
+            &["canary"],
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_unreachable_cluster() {
+        let url = stand_in().await;
+
+        let options = exporter_options(&[
+            // Nothing listens on discard port
+            "--elasticsearch_cluster=url=http://127.0.0.1:9&name=down",
+            &format!("--elasticsearch_cluster=url={}&name=up", url),
+            "--elasticsearch_pool_check_interval=50ms",
+        ]);
+
+        let ctx = Arc::new(Context {
+            exporter_options: RwLock::new(options),
+            exporters: RwLock::new(vec![None, None]),
+            admin_enabled: false,
+            probe_enabled: false,
+            web_config: Default::default(),
+            tls_reloader: None,
+        });
+
+        let down = tokio::spawn(connect(ctx.clone(), 0));
+        connect(ctx.clone(), 1).await;
+
+        // Unreachable cluster keeps retrying while healthy cluster is scraped
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let clusters = ctx
+            .exporters()
+            .iter()
+            .map(|exporter| exporter.cluster_name().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(clusters, vec!["up"]);
+
+        down.abort();
+    }
+
+    #[tokio::test]
+    async fn test_probe_credentials() {
+        let (configured, configured_authorizations) = stand_in_recording().await;
+        let (foreign, foreign_authorizations) = stand_in_recording().await;
+
+        let options = exporter_options(&[
+            &format!("--elasticsearch_url={}", configured),
+            "--elasticsearch_username=elastic",
+            "--elasticsearch_password=secret",
+        ]);
+
+        let (status, body) = probe(&options, configured.as_str()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("cluster=\"stand-in\""), "{}", body);
+        let configured_authorizations = configured_authorizations.lock().unwrap().clone();
+        assert!(!configured_authorizations.is_empty());
+        assert!(configured_authorizations
+            .iter()
+            .all(|authorization| authorization
+                .as_deref()
+                .map(|authorization| authorization.starts_with("Basic "))
+                .unwrap_or(false)));
+
+        // Credentials are never sent to targets which are not configured clusters
+        let (status, _) = probe(&options, foreign.as_str()).await;
+        assert_eq!(status, StatusCode::OK);
+        let foreign_authorizations = foreign_authorizations.lock().unwrap().clone();
+        assert!(!foreign_authorizations.is_empty());
+        assert!(foreign_authorizations
+            .iter()
+            .all(|authorization| authorization.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_probe_unreachable_target() {
+        let options = exporter_options(&[]);
+
+        // Nothing listens on discard port
+        let (status, body) = probe(&options, "http://127.0.0.1:9").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(
+            body.contains("elasticsearch_probe_success{subsystem=\"cluster_health\"} 0"),
+            "{}",
+            body
+        );
+
+        let (status, _) = probe(&options, "ftp://127.0.0.1:9").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,389 @@
+use hyper::{header::AUTHORIZATION, server::accept::Accept, Body, Request};
+use serde_derive::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error as StdError;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::rustls::{
+    internal::pemfile, AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient,
+    NoClientAuth, PrivateKey, RootCertStore, ServerConfig,
+};
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Web configuration file compatible with Prometheus exporter-toolkit
+/// https://github.com/prometheus/exporter-toolkit/blob/master/docs/web-configuration.md
+#[derive(Debug, Default, Deserialize)]
+pub struct WebConfig {
+    /// Listener TLS configuration, plain HTTP is served if not provided
+    #[serde(default)]
+    pub tls_server_config: Option<TlsServerConfig>,
+    /// Username to bcrypt password hash map
+    #[serde(default)]
+    basic_auth_users: HashMap<String, String>,
+    /// Authorization headers which passed bcrypt verification,
+    /// bcrypt is expensive by design and must not run on every scrape
+    #[serde(skip)]
+    verified: Mutex<HashSet<String>>,
+}
+
+/// Listener TLS configuration
+#[derive(Debug, Deserialize)]
+pub struct TlsServerConfig {
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    #[serde(default)]
+    client_auth_type: ClientAuthType,
+    client_ca_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+enum ClientAuthType {
+    NoClientCert,
+    VerifyClientCertIfGiven,
+    RequireAndVerifyClientCert,
+}
+
+impl Default for ClientAuthType {
+    fn default() -> Self {
+        ClientAuthType::NoClientCert
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl WebConfig {
+    /// Load web configuration file, relative paths are resolved against
+    /// configuration file directory
+    pub fn load(path: &Path) -> Result<Self, Box<dyn StdError>> {
+        let mut config: Self = serde_yaml::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| format!("Failed to parse {} err {}", path.display(), e))?;
+
+        if let Some(ref mut tls) = config.tls_server_config {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+            tls.cert_file = dir.join(&tls.cert_file);
+            tls.key_file = dir.join(&tls.key_file);
+            tls.client_ca_file = tls.client_ca_file.as_ref().map(|ca| dir.join(ca));
+
+            // Fail on start rather than on first connection
+            let _ = tls.server_config()?;
+        }
+
+        Ok(config)
+    }
+
+    /// Files configuration depends on, changes are picked up on reload
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.tls_server_config
+            .iter()
+            .flat_map(|tls| {
+                vec![tls.cert_file.clone(), tls.key_file.clone()]
+                    .into_iter()
+                    .chain(tls.client_ca_file.clone())
+            })
+            .collect()
+    }
+
+    /// Check request basic authentication against configured users,
+    /// all requests are authorized if no users are configured
+    pub async fn authorized(&self, req: &Request<Body>) -> bool {
+        if self.basic_auth_users.is_empty() {
+            return true;
+        }
+
+        let header = match req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+        {
+            Some(header) => header.to_string(),
+            None => return false,
+        };
+
+        if self
+            .verified
+            .lock()
+            .expect("verified lock")
+            .contains(&header)
+        {
+            return true;
+        }
+
+        let (username, password) = match header
+            .strip_prefix("Basic ")
+            .and_then(|encoded| base64::decode(encoded).ok())
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|decoded| {
+                decoded
+                    .split_once(':')
+                    .map(|(username, password)| (username.to_string(), password.to_string()))
+            }) {
+            Some(credentials) => credentials,
+            None => return false,
+        };
+
+        let hash = match self.basic_auth_users.get(&username) {
+            Some(hash) => hash.clone(),
+            None => return false,
+        };
+
+        let verified = tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash))
+            .await
+            .map(|verified| verified.unwrap_or(false))
+            .unwrap_or(false);
+
+        if verified {
+            let _ = self.verified.lock().expect("verified lock").insert(header);
+        }
+
+        verified
+    }
+}
+
+impl TlsServerConfig {
+    /// Rustls server configuration, certificate and key files are read on every call
+    pub fn server_config(&self) -> Result<ServerConfig, Box<dyn StdError>> {
+        let client_auth = match self.client_auth_type {
+            ClientAuthType::NoClientCert => NoClientAuth::new(),
+            ClientAuthType::VerifyClientCertIfGiven => {
+                AllowAnyAnonymousOrAuthenticatedClient::new(self.client_roots()?)
+            }
+            ClientAuthType::RequireAndVerifyClientCert => {
+                AllowAnyAuthenticatedClient::new(self.client_roots()?)
+            }
+        };
+
+        let certs = pemfile::certs(&mut BufReader::new(File::open(&self.cert_file)?))
+            .map_err(|_| invalid_data(format!("Invalid cert_file {:?}", self.cert_file)))?;
+
+        let read_keys = |read: fn(&mut dyn io::BufRead) -> Result<Vec<PrivateKey>, ()>| {
+            File::open(&self.key_file)
+                .map(BufReader::new)
+                .map(|mut reader| read(&mut reader))
+        };
+
+        let mut keys = read_keys(pemfile::pkcs8_private_keys)?.unwrap_or_default();
+        if keys.is_empty() {
+            keys = read_keys(pemfile::rsa_private_keys)?.unwrap_or_default();
+        }
+
+        let key = keys
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid_data(format!("No private key in {:?}", self.key_file)))?;
+
+        let mut config = ServerConfig::new(client_auth);
+        config.set_single_cert(certs, key)?;
+        config.set_protocols(&[b"h2".to_vec(), b"http/1.1".to_vec()]);
+
+        Ok(config)
+    }
+
+    fn client_roots(&self) -> Result<RootCertStore, Box<dyn StdError>> {
+        let path = self
+            .client_ca_file
+            .as_ref()
+            .ok_or("client_ca_file is required to verify client certificates")?;
+
+        let mut roots = RootCertStore::empty();
+        let (valid, _) = roots
+            .add_pem_file(&mut BufReader::new(File::open(path)?))
+            .map_err(|_| invalid_data(format!("Invalid client_ca_file {:?}", path)))?;
+
+        if valid == 0 {
+            return Err(invalid_data(format!("No certificates in {:?}", path)).into());
+        }
+
+        Ok(roots)
+    }
+}
+
+/// TLS acceptor shared with listener, replaced on reload so rotated
+/// certificates are served without restart
+#[derive(Clone)]
+pub struct TlsReloader(Arc<RwLock<TlsAcceptor>>);
+
+impl TlsReloader {
+    /// Build acceptor from listener TLS configuration
+    pub fn new(config: &TlsServerConfig) -> Result<Self, Box<dyn StdError>> {
+        Ok(Self(Arc::new(RwLock::new(TlsAcceptor::from(Arc::new(
+            config.server_config()?,
+        ))))))
+    }
+
+    /// Replace acceptor, established connections keep previous certificates
+    pub fn reload(&self, config: ServerConfig) {
+        *self.0.write().expect("acceptor lock") = TlsAcceptor::from(Arc::new(config));
+    }
+
+    fn acceptor(&self) -> TlsAcceptor {
+        self.0.read().expect("acceptor lock").clone()
+    }
+}
+
+impl fmt::Debug for TlsReloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TlsReloader")
+    }
+}
+
+/// TLS connections accepted for hyper server, handshakes run in
+/// background so slow clients do not block accept loop
+#[derive(Debug)]
+pub struct TlsIncoming {
+    connections: mpsc::Receiver<TlsStream<TcpStream>>,
+}
+
+impl TlsIncoming {
+    /// Bind listener and start accepting TLS connections
+    pub async fn bind(addr: SocketAddr, reloader: TlsReloader) -> Result<Self, Box<dyn StdError>> {
+        let listener = TcpListener::bind(addr).await?;
+        let (tx, connections) = mpsc::channel(128);
+
+        let _ = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        error!("accept connection err {}", e);
+                        continue;
+                    }
+                };
+
+                let _ = stream.set_nodelay(true);
+                let acceptor = reloader.acceptor();
+                let tx = tx.clone();
+
+                let _ = tokio::spawn(async move {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                    {
+                        Ok(Ok(stream)) => {
+                            let _ = tx.send(stream).await;
+                        }
+                        Ok(Err(e)) => debug!("TLS handshake with {} err {}", peer, e),
+                        Err(_) => debug!("TLS handshake with {} timed out", peer),
+                    }
+                });
+            }
+        });
+
+        Ok(Self { connections })
+    }
+}
+
+impl Accept for TlsIncoming {
+    type Conn = TlsStream<TcpStream>;
+    type Error = io::Error;
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.connections.poll_recv(cx).map(|conn| conn.map(Ok))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_rustls::{rustls::ClientConfig, webpki::DNSNameRef, TlsConnector};
+
+    #[tokio::test]
+    async fn test_basic_auth() {
+        let config: WebConfig = serde_yaml::from_str(&format!(
+            "basic_auth_users:\n  prometheus: {}\n",
+            bcrypt::hash("secret", 4).unwrap()
+        ))
+        .unwrap();
+
+        let request = |credentials: &str| {
+            Request::builder()
+                .header(
+                    AUTHORIZATION,
+                    format!("Basic {}", base64::encode(credentials)),
+                )
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        assert!(config.authorized(&request("prometheus:secret")).await);
+        // Served from verified cache
+        assert!(config.authorized(&request("prometheus:secret")).await);
+        assert!(!config.authorized(&request("prometheus:wrong")).await);
+        assert!(!config.authorized(&request("unknown:secret")).await);
+        assert!(!config.authorized(&Request::new(Body::empty())).await);
+    }
+
+    #[tokio::test]
+    async fn test_tls_reload() {
+        let tls_config = |name: &str| {
+            let files = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/files");
+            serde_yaml::from_str::<TlsServerConfig>(&format!(
+                "cert_file: {files}/{name}.crt\nkey_file: {files}/{name}.key\n",
+                files = files,
+                name = name
+            ))
+            .unwrap()
+        };
+
+        let handshake = |reloader: TlsReloader, trusted: &'static str| async move {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let _ = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let _ = reloader.acceptor().accept(stream).await;
+            });
+
+            let mut config = ClientConfig::new();
+            let _ = config
+                .root_store
+                .add_pem_file(&mut BufReader::new(
+                    File::open(format!(
+                        "{}/src/tests/files/{}.crt",
+                        env!("CARGO_MANIFEST_DIR"),
+                        trusted
+                    ))
+                    .unwrap(),
+                ))
+                .unwrap();
+
+            TlsConnector::from(Arc::new(config))
+                .connect(
+                    DNSNameRef::try_from_ascii_str("localhost").unwrap(),
+                    TcpStream::connect(addr).await.unwrap(),
+                )
+                .await
+                .is_ok()
+        };
+
+        let reloader = TlsReloader::new(&tls_config("web_a")).unwrap();
+        assert!(handshake(reloader.clone(), "web_a").await);
+        assert!(!handshake(reloader.clone(), "web_b").await);
+
+        reloader.reload(tls_config("web_b").server_config().unwrap());
+        assert!(handshake(reloader.clone(), "web_b").await);
+        assert!(!handshake(reloader, "web_a").await);
+    }
+
+    #[test]
+    fn test_unsupported_client_auth_type() {
+        let config = serde_yaml::from_str::<WebConfig>(
+            "tls_server_config:\n  cert_file: a.pem\n  key_file: a.key\n  client_auth_type: RequestClientCert\n",
+        );
+
+        assert!(config.is_err());
+    }
+}
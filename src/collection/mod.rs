@@ -1,4 +1,4 @@
-use prometheus::{default_registry, GaugeVec, IntGaugeVec, Opts};
+use prometheus::{GaugeVec, IntGaugeVec, Opts, Registry};
 use std::collections::HashMap;
 
 /// Lifetime of a metric based on heartbeat
@@ -27,6 +27,8 @@ pub struct Collection {
     pub const_labels: HashMap<String, String>,
     /// Exporter options
     options: ExporterOptions,
+    /// Registry metrics are registered to
+    registry: Registry,
     /// Metric lifetime is used to remove stale metrics
     pub gauges_lifetime: lifetime::MetricLifetimeMap,
     /// Metric lifetime is used to remove stale metrics
@@ -35,11 +37,12 @@ pub struct Collection {
 
 impl Collection {
     /// Initialize collection with given exporter options and subsystem,
-    /// such as: cat_indices, cat_shards, etc.
-    pub fn new(subsystem: &'static str, options: ExporterOptions) -> Self {
+    /// such as: cat_indices, cat_shards, etc., metrics are registered to given registry
+    pub fn new(subsystem: &'static str, options: ExporterOptions, registry: Registry) -> Self {
         Self {
             subsystem,
             options,
+            registry,
             skip_metrics: vec![],
             skip_labels: vec![],
             include_labels: vec![],
@@ -111,7 +114,7 @@ impl Collection {
             let _ = set_labels(&new_fgauge, &mut self.fgauges_lifetime)?;
 
             // Register new metric
-            default_registry().register(Box::new(new_fgauge.clone()))?;
+            self.registry.register(Box::new(new_fgauge.clone()))?;
 
             let _ = self.fgauges.insert(key.to_string(), new_fgauge);
         }
@@ -178,7 +181,7 @@ impl Collection {
             let _ = set_labels(&new_gauge, &mut self.gauges_lifetime)?;
 
             // Register new metric
-            default_registry().register(Box::new(new_gauge.clone()))?;
+            self.registry.register(Box::new(new_gauge.clone()))?;
 
             let _ = self.gauges.insert(key.to_string(), new_gauge);
         }
@@ -284,6 +287,25 @@ impl Collection {
     }
 }
 
+impl Drop for Collection {
+    fn drop(&mut self) {
+        // Collection is dropped once subsystem poller is stopped, unregister
+        // its metrics so they are not exported anymore and can be registered
+        // again if subsystem is restarted
+        for gauge in self.gauges.values() {
+            if let Err(e) = self.registry.unregister(Box::new(gauge.clone())) {
+                error!("{} unregister gauge err {}", self.subsystem, e);
+            }
+        }
+
+        for fgauge in self.fgauges.values() {
+            if let Err(e) = self.registry.unregister(Box::new(fgauge.clone())) {
+                error!("{} unregister fgauge err {}", self.subsystem, e);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
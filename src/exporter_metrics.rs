@@ -1,4 +1,4 @@
-use prometheus::HistogramVec;
+use prometheus::{HistogramVec, IntCounterVec, IntGaugeVec};
 
 lazy_static! {
     /// Subsystem requests histogram
@@ -8,4 +8,28 @@ lazy_static! {
         &["subsystem", "cluster"]
     )
     .expect("valid histogram vec metric");
+
+    /// Connection pool node liveness
+    pub static ref POOL_NODE_ALIVE: IntGaugeVec = register_int_gauge_vec!(
+        "elasticsearch_pool_node_alive",
+        "Whether Elasticsearch node of connection pool is alive.",
+        &["cluster", "node"]
+    )
+    .expect("valid int gauge vec metric");
+
+    /// Connection pool node failed health checks
+    pub static ref POOL_NODE_FAILURES: IntCounterVec = register_int_counter_vec!(
+        "elasticsearch_pool_node_failures_total",
+        "Failed health checks of Elasticsearch node of connection pool.",
+        &["cluster", "node"]
+    )
+    .expect("valid int counter vec metric");
+
+    /// Connection pool sniffing results
+    pub static ref POOL_SNIFF: IntCounterVec = register_int_counter_vec!(
+        "elasticsearch_pool_sniff_total",
+        "Elasticsearch node sniffing attempts of connection pool.",
+        &["cluster", "result"]
+    )
+    .expect("valid int counter vec metric");
 }
@@ -47,11 +47,16 @@ extern crate lazy_static;
 extern crate log;
 #[macro_use]
 extern crate serde_derive;
+use elasticsearch::http::response::Response;
 use elasticsearch::http::transport::{SingleNodeConnectionPool, TransportBuilder};
 use elasticsearch::Elasticsearch;
-use std::collections::{BTreeMap, HashMap};
-use std::sync::Arc;
+use prometheus::{proto::MetricFamily, IntGaugeVec, Opts, Registry};
+use std::collections::{btree_map::Entry, BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
+use tokio::task::JoinHandle;
+use url::Url;
 
 /// Generic collector of Elasticsearch metrics
 pub mod collection;
@@ -60,9 +65,16 @@ pub mod metric;
 
 /// Exporter metrics
 mod exporter_metrics;
+use exporter_metrics::{POOL_NODE_ALIVE, POOL_NODE_FAILURES, POOL_SNIFF};
+
+/// Elasticsearch nodes connection pool
+mod pool;
 
 mod options;
-pub use options::ExporterOptions;
+pub use options::{ClusterOptions, ExporterOptions, QueryOptions};
+
+/// Elasticsearch authentication and TLS
+pub mod credentials;
 
 /// Reserved labels
 pub mod reserved;
@@ -86,6 +98,110 @@ pub type ExporterPollIntervals = HashMap<String, Duration>;
 /// Exporter metrics switch ON/OFF
 pub type ExporterMetricsSwitch = BTreeMap<String, bool>;
 
+/// Gather metrics of default registry and given exporters registries,
+/// metric families of the same name are merged
+pub fn gather(exporters: &[Exporter]) -> Vec<MetricFamily> {
+    let mut families: BTreeMap<String, MetricFamily> = BTreeMap::new();
+
+    let gathered = exporters
+        .iter()
+        .map(|exporter| exporter.registry().gather())
+        .chain(std::iter::once(prometheus::gather()));
+
+    for mut family in gathered.flatten() {
+        match families.entry(family.get_name().to_string()) {
+            Entry::Occupied(mut entry) => {
+                entry
+                    .get_mut()
+                    .mut_metric()
+                    .extend(family.take_metric().into_vec());
+            }
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(family);
+            }
+        }
+    }
+
+    families.into_values().collect()
+}
+
+/// Dispatch subsystem name to its metrics module, e.g.:
+/// `dispatch_subsystem!("cat_health", metric => metric::SUBSYSTEM)`
+macro_rules! dispatch_subsystem {
+    ($subsystem:expr, $metric:ident => $body:expr) => {
+        dispatch_subsystem!(@dispatch $subsystem, $metric => $body;
+            // =^.^=
+            _cat::allocation,
+            _cat::shards,
+            _cat::indices,
+            _cat::segments,
+            _cat::nodes,
+            _cat::recovery,
+            _cat::health,
+            _cat::pending_tasks,
+            _cat::aliases,
+            _cat::thread_pool,
+            _cat::plugins,
+            _cat::fielddata,
+            _cat::nodeattrs,
+            _cat::repositories,
+            _cat::templates,
+            _cat::transforms,
+            // /_cluster
+            _cluster::health,
+            _cluster::stats,
+            _cluster::pending_tasks,
+            _cluster::state,
+            _cluster::allocation_explain,
+            _cluster::remote_info,
+            _cluster::settings,
+            // canary /_doc, /_search
+            _canary::probe,
+            // /_ccr
+            _ccr::stats,
+            // /_data_stream
+            _data_stream::stats,
+            // /_ilm
+            _ilm::explain,
+            _ilm::indices,
+            // /_license
+            _license::license,
+            // /_migration
+            _migration::deprecations,
+            // /_nodes
+            _nodes::usage,
+            _nodes::stats,
+            _nodes::info,
+            _nodes::versions,
+            // /_search
+            _search::freshness,
+            _search::queries,
+            // /_settings
+            _settings::index_blocks,
+            // /_snapshot, /_slm
+            _snapshot::snapshots,
+            // /_ssl
+            _ssl::certificates,
+            // /_stats
+            _stats::_all,
+            // /_tasks
+            _tasks::list
+        )
+    };
+    (@dispatch $subsystem:expr, $metric:ident => $body:expr; $($namespace:ident::$module:ident),+) => {
+        match $subsystem {
+            $(
+                subsystem if subsystem == $crate::metrics::$namespace::$module::SUBSYSTEM => {
+                    use $crate::metrics::$namespace::$module as $metric;
+
+                    Some($body)
+                }
+            )+
+            _ => None,
+        }
+    };
+}
+
 /// Elasticsearch exporter
 #[derive(Debug, Clone)]
 pub struct Exporter(Arc<Inner>);
@@ -94,22 +210,54 @@ pub struct Exporter(Arc<Inner>);
 struct Inner {
     /// Name of Elasticsearch cluster exporter is working
     cluster_name: String,
-    /// Elasticsearch client instance
-    client: Elasticsearch,
-    /// Exporter options
-    options: ExporterOptions,
+    /// Elasticsearch nodes, clients are rebuilt when credentials change
+    pool: pool::NodePool,
+    /// Credentials and certificates client was built with
+    secrets: Mutex<credentials::ClientSecrets>,
+    /// Exporter options, replaced on reload
+    options: RwLock<Arc<ExporterOptions>>,
     /// Constant exporter labels, e.g.: cluster
     const_labels: HashMap<String, String>,
+    /// Registry of cluster metrics, separate per exporter to avoid
+    /// metric collisions between clusters
+    registry: Registry,
 
     /// Node ID to node name map for adding extra metadata labels
     /// {"U-WnGaTpRxucgde3miiDWw": "m1-supernode.example.com"}
     nodes_metadata: metadata::IdToMetadata,
+
+    /// Running subsystem pollers
+    pollers: Mutex<HashMap<&'static str, JoinHandle<()>>>,
+    /// Node metadata refresh poller
+    metadata_poller: Mutex<Option<JoinHandle<()>>>,
+    /// Last values of queries subsystem searches
+    queries_cache: metrics::_search::queries::QueryCache,
+    /// States of enum-like cluster settings seen by cluster_settings subsystem
+    cluster_settings_states: metrics::_cluster::settings::SeenStates,
+    /// Routings, failure counters and last cleanup of canary subsystem
+    canary_state: metrics::_canary::probe::SharedState,
 }
 
 impl Exporter {
-    /// Elasticsearch client instance
-    pub fn client(&self) -> &Elasticsearch {
-        &self.0.client
+    /// Elasticsearch client instance of next alive node
+    pub fn client(&self) -> Elasticsearch {
+        self.0.pool.next().client.clone()
+    }
+
+    /// Send idempotent request, e.g.: GET, request failed without response
+    /// is retried on the next alive node of the pool
+    pub async fn send<F, Fut>(&self, request: F) -> Result<Response, elasticsearch::Error>
+    where
+        F: Fn(Elasticsearch) -> Fut,
+        Fut: Future<Output = Result<Response, elasticsearch::Error>>,
+    {
+        self.0.pool.send(request).await
+    }
+
+    /// Report failed request without response, connection pool health
+    /// check is run to mark unreachable nodes dead
+    pub fn connection_failed(&self) {
+        self.0.pool.recheck()
     }
 
     /// Elasticsearch cluster name
@@ -117,9 +265,9 @@ impl Exporter {
         &self.0.cluster_name
     }
 
-    /// Exporter options
-    pub fn options(&self) -> &ExporterOptions {
-        &self.0.options
+    /// Exporter options, subsystem pollers keep options they were started with
+    pub fn options(&self) -> Arc<ExporterOptions> {
+        self.0.options.read().expect("options lock").clone()
     }
 
     /// Exporter options
@@ -127,22 +275,98 @@ impl Exporter {
         self.0.const_labels.clone()
     }
 
+    /// Registry of cluster metrics
+    pub fn registry(&self) -> &Registry {
+        &self.0.registry
+    }
+
     /// Node ID to node name map for adding extra metadata labels
     /// {"U-WnGaTpRxucgde3miiDWw": "m1-supernode.example.com"}
     pub fn nodes_metadata(&self) -> &metadata::IdToMetadata {
         &self.0.nodes_metadata
     }
 
+    /// Last values of queries subsystem searches
+    pub(crate) fn queries_cache(&self) -> &metrics::_search::queries::QueryCache {
+        &self.0.queries_cache
+    }
+
+    /// States of enum-like cluster settings seen by cluster_settings subsystem
+    pub(crate) fn cluster_settings_states(&self) -> &metrics::_cluster::settings::SeenStates {
+        &self.0.cluster_settings_states
+    }
+
+    /// Routings, failure counters and last cleanup of canary subsystem
+    pub(crate) fn canary_state(&self) -> &metrics::_canary::probe::SharedState {
+        &self.0.canary_state
+    }
+
+    fn build_nodes(
+        urls: &[Url],
+        options: &ExporterOptions,
+        secrets: &credentials::ClientSecrets,
+    ) -> Result<Vec<pool::Node>, Box<dyn std::error::Error>> {
+        let mut nodes = Vec::with_capacity(urls.len());
+
+        for url in urls.iter() {
+            let connection_pool = SingleNodeConnectionPool::new(url.clone());
+            let transport = TransportBuilder::new(connection_pool)
+                .timeout(options.elasticsearch_global_timeout);
+
+            nodes.push(pool::Node::new(
+                url.clone(),
+                Elasticsearch::new(secrets.configure(transport)?.build()?),
+            ));
+        }
+
+        Ok(nodes)
+    }
+
     /// Spawn exporter
     pub async fn new(options: ExporterOptions) -> Result<Self, Box<dyn std::error::Error>> {
-        let connection_pool = SingleNodeConnectionPool::new(options.elasticsearch_url.clone());
-        let transport = TransportBuilder::new(connection_pool)
-            .timeout(options.elasticsearch_global_timeout)
-            .build()?;
+        let secrets = credentials::ClientSecrets::load(&options)?;
+
+        let pool = pool::NodePool::new(Self::build_nodes(
+            &options.elasticsearch_urls(),
+            &options,
+            &secrets,
+        )?);
+
+        let mut ping_error = None;
+        for node in pool.nodes() {
+            info!("Elasticsearch: ping {}", node.label());
+            if let Err(e) = node.client.ping().send().await {
+                error!("ping {} err {}", node.label(), e);
+                node.set_alive(false);
+                ping_error = Some(e);
+            }
+        }
+
+        if !pool.nodes().iter().any(|node| node.is_alive()) {
+            if let Some(e) = ping_error {
+                return Err(e.into());
+            }
+        }
 
-        let client = Elasticsearch::new(transport);
-        info!("Elasticsearch: ping");
-        let _ = client.ping().send().await?;
+        if options.elasticsearch_sniff {
+            let node = pool.next();
+
+            // Seed nodes are used until pool check sniffs successfully
+            match pool::sniff(&node.client, &node.url).await {
+                Ok(urls) if !urls.is_empty() => {
+                    info!("Elasticsearch: sniffed {} nodes", urls.len());
+                    pool.replace(Self::build_nodes(&urls, &options, &secrets)?);
+                }
+                Ok(_) => info!("Elasticsearch: sniffed no nodes, using seed nodes"),
+                Err(e) => error!(
+                    "sniff nodes via {} err {}, using seed nodes",
+                    node.label(),
+                    e
+                ),
+            }
+        }
+
+        let client = pool.next().client.clone();
 
         let nodes_metadata = if options.enable_metadata_refresh() {
             metadata::node_data::build(&client).await?
@@ -152,101 +376,416 @@ impl Exporter {
             Default::default()
         };
 
-        let cluster_name = metadata::cluster_name(&client).await?;
+        let cluster_name = match options.elasticsearch_cluster_name {
+            Some(ref cluster_name) => cluster_name.clone(),
+            None => metadata::cluster_name(&client).await?,
+        };
 
         let mut const_labels = HashMap::new();
         let _ = const_labels.insert("cluster".into(), cluster_name.clone());
 
         Ok(Self(Arc::new(Inner {
             cluster_name,
-            client,
-            options,
+            pool,
+            secrets: Mutex::new(secrets),
+            options: RwLock::new(Arc::new(options)),
             const_labels,
+            registry: Registry::new(),
             nodes_metadata,
+            pollers: Mutex::new(HashMap::new()),
+            metadata_poller: Mutex::new(None),
+            queries_cache: Default::default(),
+            cluster_settings_states: Default::default(),
+            canary_state: Default::default(),
         })))
     }
 
     /// Spawn collectors
     pub async fn spawn(self) {
-        self.spawn_cat();
-        self.spawn_cluster();
-        self.spawn_nodes();
-        self.spawn_stats();
-
-        if self.options().enable_metadata_refresh() {
-            let _ = tokio::spawn(metadata::node_data::poll(self.clone()));
-        }
-    }
-
-    fn spawn_cluster(&self) {
-        use metrics::_cluster::*;
-
-        is_metric_enabled!(self.clone(), health);
-    }
-
-    fn spawn_stats(&self) {
-        use metrics::_stats::*;
-
-        is_metric_enabled!(self.clone(), _all);
-    }
-
-    fn spawn_nodes(&self) {
-        use metrics::_nodes::*;
-
-        is_metric_enabled!(self.clone(), usage);
-        is_metric_enabled!(self.clone(), stats);
-        is_metric_enabled!(self.clone(), info);
-    }
-
-    // =^.^=
-    // /_cat/allocation
-    // /_cat/shards
-    // /_cat/indices
-    // /_cat/segments
-    // /_cat/nodes
-    // /_cat/recovery
-    // /_cat/health
-    // /_cat/pending_tasks
-    // /_cat/aliases
-    // /_cat/thread_pool
-    // /_cat/plugins
-    // /_cat/fielddata
-    // /_cat/nodeattrs
-    // /_cat/repositories
-    // /_cat/templates
-    // /_cat/transforms
-    fn spawn_cat(&self) {
-        use metrics::_cat::*;
-
-        is_metric_enabled!(self.clone(), allocation);
-        is_metric_enabled!(self.clone(), shards);
-        is_metric_enabled!(self.clone(), indices);
-        is_metric_enabled!(self.clone(), segments);
-        is_metric_enabled!(self.clone(), nodes);
-        is_metric_enabled!(self.clone(), recovery);
-        is_metric_enabled!(self.clone(), health);
-        is_metric_enabled!(self.clone(), pending_tasks);
-        is_metric_enabled!(self.clone(), aliases);
-        is_metric_enabled!(self.clone(), thread_pool);
-        is_metric_enabled!(self.clone(), plugins);
-        is_metric_enabled!(self.clone(), fielddata);
-        is_metric_enabled!(self.clone(), nodeattrs);
-        is_metric_enabled!(self.clone(), repositories);
-        is_metric_enabled!(self.clone(), templates);
-        is_metric_enabled!(self.clone(), transforms);
+        if self.options().has_credential_files() {
+            let _ = tokio::spawn(self.clone().refresh_credentials());
+        }
+
+        if self.options().elasticsearch_sniff || self.0.pool.nodes().len() > 1 {
+            let _ = tokio::spawn(self.clone().check_pool());
+        }
+
+        for subsystem in ExporterOptions::subsystems() {
+            if self.options().is_metric_enabled(subsystem) {
+                let _ = self.start_subsystem(subsystem);
+            }
+        }
     }
 
-    pub(crate) fn random_delay() -> u64 {
-        oorandom::Rand64::new(292).rand_range(150..800)
+    /// Probe subsystems of given target once, blackbox exporter style. Metrics
+    /// are collected into fresh registry, default registry is not touched
+    pub async fn probe(
+        options: &ExporterOptions,
+        target: Url,
+        subsystems: &[&str],
+    ) -> Result<Vec<MetricFamily>, Box<dyn std::error::Error>> {
+        let known_subsystems = ExporterOptions::subsystems();
+
+        if let Some(subsystem) = subsystems
+            .iter()
+            .find(|subsystem| !known_subsystems.contains(subsystem))
+        {
+            return Err(format!("Unknown subsystem {}", subsystem).into());
+        }
+
+        if let Some(subsystem) = subsystems
+            .iter()
+            .find(|subsystem| ExporterOptions::probe_excluded_subsystems().contains(subsystem))
+        {
+            return Err(format!("Subsystem {} can't be probed", subsystem).into());
+        }
+
+        let mut options = options.probe_options(&target);
+        options.exporter_metrics_enabled = subsystems
+            .iter()
+            .map(|subsystem| (subsystem.to_string(), true))
+            .collect();
+
+        // Unreachable target is a failed probe, not a failed request
+        let exporter = match Self::new(options).await {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                error!("probe {} err {}", credentials::redact_url(&target), e);
+
+                let registry = Registry::new();
+                let probe_success = Self::probe_success(&registry, HashMap::new())?;
+                for subsystem in subsystems {
+                    probe_success.with_label_values(&[subsystem]).set(0);
+                }
+
+                return Ok(registry.gather());
+            }
+        };
+
+        let probe_success = Self::probe_success(exporter.registry(), exporter.const_labels())?;
+
+        // Collections unregister their metrics on drop,
+        // keep them until registry is gathered
+        let mut collections = Vec::new();
+
+        for subsystem in subsystems {
+            let result = dispatch_subsystem!(*subsystem, metric => metric::probe(&exporter).await);
+
+            match result {
+                Some(Ok(collection)) => {
+                    collections.push(collection);
+                    probe_success.with_label_values(&[subsystem]).set(1);
+                }
+                Some(Err(e)) => {
+                    error!("probe {} metrics err {}", subsystem, e);
+                    probe_success.with_label_values(&[subsystem]).set(0);
+                }
+                None => {}
+            }
+        }
+
+        Ok(exporter.registry().gather())
     }
-}
 
-/// Convenience macro to poll metrics
-#[macro_export]
-macro_rules! is_metric_enabled {
-    ($exporter:expr, $metric:ident) => {
-        if $exporter.options().is_metric_enabled($metric::SUBSYSTEM) {
-            let _ = tokio::spawn($metric::poll($exporter.clone()));
+    fn probe_success(
+        registry: &Registry,
+        const_labels: HashMap<String, String>,
+    ) -> Result<IntGaugeVec, prometheus::Error> {
+        let probe_success = IntGaugeVec::new(
+            Opts::new("probe_success", "Whether subsystem probe succeeded")
+                .const_labels(const_labels)
+                .namespace(NAMESPACE),
+            &["subsystem"],
+        )?;
+        registry.register(Box::new(probe_success.clone()))?;
+
+        Ok(probe_success)
+    }
+
+    /// Start subsystem poller, returns false if subsystem is unknown or already running
+    pub fn start_subsystem(&self, subsystem: &str) -> bool {
+        let mut pollers = self.0.pollers.lock().expect("pollers lock");
+
+        if pollers.contains_key(subsystem) {
+            return false;
         }
-    };
+
+        let poller = dispatch_subsystem!(subsystem, metric => {
+            (metric::SUBSYSTEM, tokio::spawn(metric::poll(self.clone())))
+        });
+
+        match poller {
+            Some((subsystem, handle)) => {
+                let _ = pollers.insert(subsystem, handle);
+                drop(pollers);
+
+                // /_nodes and /_tasks subsystems rely on node metadata labels
+                if ExporterOptions::metadata_subsystems().contains(&subsystem) {
+                    self.spawn_metadata_refresh();
+                }
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop subsystem poller and unregister its metrics, returns false if
+    /// subsystem is not running
+    pub async fn stop_subsystem(&self, subsystem: &str) -> bool {
+        let poller = self
+            .0
+            .pollers
+            .lock()
+            .expect("pollers lock")
+            .remove(subsystem);
+
+        let handle = match poller {
+            Some(handle) => handle,
+            None => return false,
+        };
+
+        info!("Stopping subsystem: {}", subsystem);
+        // Dropping aborted poller drops its collection, which unregisters
+        // subsystem metrics, wait for it before metrics are registered again
+        handle.abort();
+        let _ = handle.await;
+
+        let metadata_subsystems = ExporterOptions::metadata_subsystems();
+        if !self
+            .running_subsystems()
+            .iter()
+            .any(|running| metadata_subsystems.contains(running))
+        {
+            let _ = self.stop_metadata_refresh().await;
+        }
+
+        true
+    }
+
+    /// Currently running subsystems
+    pub fn running_subsystems(&self) -> Vec<&'static str> {
+        let mut subsystems = self
+            .0
+            .pollers
+            .lock()
+            .expect("pollers lock")
+            .keys()
+            .copied()
+            .collect::<Vec<&'static str>>();
+
+        subsystems.sort_unstable();
+        subsystems
+    }
+
+    /// Apply reloaded options, pollers of subsystems with changed options are
+    /// restarted so their metrics are registered anew, returns restarted subsystems.
+    /// Options applied only on start must not change
+    pub async fn reload(&self, options: ExporterOptions) -> Result<Vec<&'static str>, String> {
+        let current = self.options();
+
+        let restart_required = current.restart_required(&options);
+        if !restart_required.is_empty() {
+            return Err(format!(
+                "{} change requires restart",
+                restart_required.join(", ")
+            ));
+        }
+
+        let changed = current.changed_subsystems(&options);
+        let metadata_changed = current.exporter_metadata_refresh_interval
+            != options.exporter_metadata_refresh_interval;
+
+        *self.0.options.write().expect("options lock") = Arc::new(options);
+
+        // Restart with new interval only if metadata was refreshed
+        if metadata_changed && self.stop_metadata_refresh().await {
+            self.spawn_metadata_refresh();
+        }
+
+        let options = self.options();
+        let mut restarted = Vec::new();
+
+        for subsystem in changed {
+            let was_running = self.stop_subsystem(subsystem).await;
+
+            // Subsystems switched at runtime keep their state unless switch changed
+            let start =
+                if current.is_metric_enabled(subsystem) != options.is_metric_enabled(subsystem) {
+                    options.is_metric_enabled(subsystem)
+                } else {
+                    was_running
+                };
+
+            if start && self.start_subsystem(subsystem) {
+                restarted.push(subsystem);
+            }
+        }
+
+        Ok(restarted)
+    }
+
+    /// Stop metadata refresh, returns false if metadata was not refreshed
+    async fn stop_metadata_refresh(&self) -> bool {
+        let metadata_poller = self.0.metadata_poller.lock().expect("metadata lock").take();
+
+        match metadata_poller {
+            Some(handle) => {
+                handle.abort();
+                let _ = handle.await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn spawn_metadata_refresh(&self) {
+        let mut metadata_poller = self.0.metadata_poller.lock().expect("metadata lock");
+
+        if metadata_poller.is_none() {
+            *metadata_poller = Some(tokio::spawn(metadata::node_data::poll(self.clone())));
+        }
+    }
+
+    /// Re-read credential and certificate files, rebuild client on change
+    async fn refresh_credentials(self) {
+        let mut interval =
+            tokio::time::interval(self.options().elasticsearch_credentials_refresh_interval);
+
+        loop {
+            let _ = interval.tick().await;
+
+            let secrets = match credentials::ClientSecrets::load(&self.options()) {
+                Ok(secrets) => secrets,
+                Err(e) => {
+                    error!("read credential files err {}", e);
+                    continue;
+                }
+            };
+
+            let mut current = self.0.secrets.lock().expect("secrets lock");
+
+            if *current == secrets {
+                continue;
+            }
+
+            let urls = self
+                .0
+                .pool
+                .nodes()
+                .iter()
+                .map(|node| node.url.clone())
+                .collect::<Vec<Url>>();
+
+            match Self::build_nodes(&urls, &self.options(), &secrets) {
+                Ok(nodes) => {
+                    info!("Credential files changed, rebuilding clients");
+                    self.0.pool.replace(nodes);
+                    *current = secrets;
+                }
+                Err(e) => {
+                    error!("rebuild client err {}", e);
+                }
+            }
+        }
+    }
+
+    /// Sniff cluster nodes and ping pool nodes, dead nodes are revived once they respond
+    async fn check_pool(self) {
+        let mut interval = tokio::time::interval(self.options().elasticsearch_pool_check_interval);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = self.0.pool.recheck_requested() => {
+                    debug!("Connection failure reported, checking pool");
+                },
+            }
+
+            if self.options().elasticsearch_sniff {
+                self.sniff().await;
+            }
+
+            for node in self.0.pool.nodes() {
+                let alive = node.client.ping().send().await.is_ok();
+
+                if node.is_alive() != alive {
+                    info!(
+                        "Elasticsearch node {} is {}",
+                        node.label(),
+                        if alive { "alive" } else { "dead" }
+                    );
+                }
+
+                node.set_alive(alive);
+
+                let labels = [self.cluster_name(), &node.label()];
+                POOL_NODE_ALIVE.with_label_values(&labels).set(alive as i64);
+                if !alive {
+                    POOL_NODE_FAILURES.with_label_values(&labels).inc();
+                }
+            }
+        }
+    }
+
+    async fn sniff(&self) {
+        let node = self.0.pool.next();
+
+        let urls = match pool::sniff(&node.client, &node.url).await {
+            Ok(urls) if !urls.is_empty() => urls,
+            Ok(_) => {
+                POOL_SNIFF
+                    .with_label_values(&[self.cluster_name(), "empty"])
+                    .inc();
+                return;
+            }
+            Err(e) => {
+                error!("sniff nodes via {} err {}", node.label(), e);
+                POOL_SNIFF
+                    .with_label_values(&[self.cluster_name(), "error"])
+                    .inc();
+                return;
+            }
+        };
+
+        POOL_SNIFF
+            .with_label_values(&[self.cluster_name(), "success"])
+            .inc();
+
+        let current = self.0.pool.nodes();
+        if current.len() == urls.len()
+            && current
+                .iter()
+                .zip(urls.iter())
+                .all(|(node, url)| &node.url == url)
+        {
+            return;
+        }
+
+        let secrets = self.0.secrets.lock().expect("secrets lock").clone();
+
+        match Self::build_nodes(&urls, &self.options(), &secrets) {
+            Ok(nodes) => {
+                info!("Elasticsearch: sniffed {} nodes", nodes.len());
+
+                for node in current.iter() {
+                    if !urls.contains(&node.url) {
+                        let _ = POOL_NODE_ALIVE
+                            .remove_label_values(&[self.cluster_name(), &node.label()]);
+                        let _ = POOL_NODE_FAILURES
+                            .remove_label_values(&[self.cluster_name(), &node.label()]);
+                    }
+                }
+
+                self.0.pool.replace(nodes);
+            }
+            Err(e) => {
+                error!("build sniffed nodes err {}", e);
+            }
+        }
+    }
+
+    pub(crate) fn random_delay() -> u64 {
+        oorandom::Rand64::new(292).rand_range(150..800)
+    }
 }
@@ -1,3 +1,4 @@
+use elasticsearch::http::response::Response;
 use elasticsearch::nodes::NodesInfoParts;
 use elasticsearch::{Elasticsearch, Error};
 use std::collections::HashMap;
@@ -10,20 +11,20 @@ pub(crate) type IdToMetadata = RwLock<NodeDataMap>;
 pub(crate) type NodeDataMap = HashMap<String, NodeData>;
 
 pub(crate) async fn build(client: &Elasticsearch) -> Result<IdToMetadata, Error> {
-    Ok(RwLock::new(_build(client).await?))
+    Ok(RwLock::new(_build(nodes_os(client.clone()).await?).await?))
 }
 
-pub(crate) async fn _build(client: &Elasticsearch) -> Result<NodeDataMap, Error> {
+async fn nodes_os(client: Elasticsearch) -> Result<Response, Error> {
     info!("Elasticsearch: fetching cluster metadata");
-    let nodes_os = client
+    client
         .nodes()
         .info(NodesInfoParts::Metric(&["os"]))
         .send()
-        .await?
-        .json::<NodesOs>()
-        .await?;
+        .await
+}
 
-    Ok(nodes_os.nodes)
+pub(crate) async fn _build(response: Response) -> Result<NodeDataMap, Error> {
+    Ok(response.json::<NodesOs>().await?.nodes)
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,7 +33,7 @@ struct NodesOs {
 }
 
 /// Node metadata
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default)]
 pub struct NodeData {
     /// Node FQDN
     pub name: String,
@@ -42,11 +43,10 @@ pub struct NodeData {
     pub version: String,
 }
 
+/// Replace metadata with refreshed one, nodes which left cluster are removed
 #[inline]
-fn update_map(old: &mut NodeDataMap, new: NodeDataMap) {
-    for (key, new_metadata) in new.into_iter() {
-        let _ = old.insert(key, new_metadata);
-    }
+pub(crate) fn update_map(old: &mut NodeDataMap, new: NodeDataMap) {
+    *old = new;
 }
 
 #[allow(unused)]
@@ -63,7 +63,12 @@ pub(crate) async fn poll(exporter: Exporter) {
             .with_label_values(&["/_nodes/os", exporter.cluster_name()])
             .start_timer();
 
-        match _build(exporter.client()).await {
+        let new_metadata = match exporter.send(nodes_os).await {
+            Ok(response) => _build(response).await,
+            Err(e) => Err(e),
+        };
+
+        match new_metadata {
             Ok(new_metadata) => {
                 update_map(&mut *exporter.nodes_metadata().write().await, new_metadata)
             }
@@ -105,9 +110,14 @@ mod tests {
             },
         );
 
-        update_map(&mut old, new);
+        update_map(&mut old, new.clone());
 
         // Version is updated
         assert_eq!(old.get(&test_key).unwrap().version, "7.9.3");
+
+        // Node left cluster
+        let _ = new.remove(&test_key);
+        update_map(&mut old, new);
+        assert!(!old.contains_key(&test_key));
     }
 }
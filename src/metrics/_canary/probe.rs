@@ -0,0 +1,516 @@
+use elasticsearch::indices::{IndicesCreateParts, IndicesExistsParts};
+use elasticsearch::{
+    DeleteByQueryParts, Elasticsearch, IndexParts, SearchParts, SearchShardsParts,
+};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use super::responses::{CanarySearchResponse, SearchShardsResponse};
+
+/// Opt-in synthetic probe, indexes document into each primary shard of
+/// `exporter_canary_index` and searches until the document is visible
+pub(crate) const SUBSYSTEM: &str = "canary";
+
+/// Interval between searches of indexed canary document
+const VISIBILITY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Routing values tried per shard while resolving routing of each shard
+const ROUTING_CANDIDATES_PER_SHARD: usize = 32;
+
+/// Canary state of exporter kept between polls
+pub(crate) type SharedState = Mutex<CanaryState>;
+
+/// Canary state kept between polls
+#[derive(Debug, Default)]
+pub(crate) struct CanaryState {
+    /// Canary index routings were resolved for
+    index: String,
+    /// Routing value of each shard
+    routings: BTreeMap<u64, String>,
+    write_failures: HashMap<u64, u64>,
+    visibility_failures: HashMap<u64, u64>,
+    /// Last removal of old canary documents
+    cleaned: Option<Instant>,
+}
+
+/// Latencies of single canary probe, missing latency means failure
+#[derive(Debug, Default)]
+pub(crate) struct CanaryProbe {
+    /// Time until index request was acknowledged
+    write_latency: Option<Duration>,
+    /// Time since index request until document was visible to search
+    visibility_latency: Option<Duration>,
+}
+
+/// Create canary index unless it exists, existing index settings are not changed.
+/// `@timestamp` is mapped as date explicitly, dynamic mapping of epoch millis is long
+async fn ensure_index(
+    client: &Elasticsearch,
+    index: &str,
+    shards: usize,
+    timeout: Duration,
+) -> Result<(), elasticsearch::Error> {
+    let exists = client
+        .indices()
+        .exists(IndicesExistsParts::Index(&[index]))
+        .request_timeout(timeout)
+        .send()
+        .await?;
+
+    if exists.status_code().is_success() {
+        return Ok(());
+    }
+
+    let _ = client
+        .indices()
+        .create(IndicesCreateParts::Index(index))
+        .body(json!({
+            "settings": {
+                "index": {
+                    "number_of_shards": shards,
+                    "auto_expand_replicas": "0-1"
+                }
+            },
+            "mappings": {
+                "properties": {
+                    "@timestamp": { "type": "date", "format": "epoch_millis" }
+                }
+            }
+        }))
+        .request_timeout(timeout)
+        .send()
+        .await?
+        .error_for_status_code()?;
+
+    Ok(())
+}
+
+/// Find routing value routed to each of shards by asking /_search_shards,
+/// known routings are kept
+async fn resolve_routings(
+    client: &Elasticsearch,
+    index: &str,
+    shards: &[u64],
+    mut routings: BTreeMap<u64, String>,
+    timeout: Duration,
+) -> Result<BTreeMap<u64, String>, elasticsearch::Error> {
+    routings.retain(|shard, _| shards.contains(shard));
+
+    let candidates = shards.len() * ROUTING_CANDIDATES_PER_SHARD;
+
+    for candidate in 0..candidates {
+        if shards.iter().all(|shard| routings.contains_key(shard)) {
+            break;
+        }
+
+        let routing = candidate.to_string();
+
+        let shard = client
+            .search_shards(SearchShardsParts::Index(&[index]))
+            .routing(&routing)
+            .request_timeout(timeout)
+            .send()
+            .await?
+            .json::<SearchShardsResponse>()
+            .await?
+            .routed_shard();
+
+        if let Some(shard) = shard {
+            let _ = routings.entry(shard).or_insert(routing);
+        }
+    }
+
+    Ok(routings)
+}
+
+/// Index canary document with routing and search for it until visible
+/// or visibility timeout elapses
+pub(crate) async fn probe_shard(
+    client: &Elasticsearch,
+    index: &str,
+    routing: &str,
+    timeout: Duration,
+    visibility_timeout: Duration,
+) -> CanaryProbe {
+    let now = chrono::Utc::now();
+    let id = format!("{}-{}", routing, now.timestamp_millis());
+
+    let started = Instant::now();
+
+    let indexed = client
+        .index(IndexParts::IndexId(index, &id))
+        .routing(routing)
+        .body(json!({ "@timestamp": now.timestamp_millis(), "routing": routing }))
+        .request_timeout(timeout)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status_code());
+
+    if let Err(e) = indexed {
+        error!(
+            "{} index {} routing {} err {}",
+            SUBSYSTEM, index, routing, e
+        );
+        return CanaryProbe::default();
+    }
+
+    let mut result = CanaryProbe {
+        write_latency: Some(started.elapsed()),
+        visibility_latency: None,
+    };
+
+    loop {
+        let response = client
+            .search(SearchParts::Index(&[index]))
+            .routing(&[routing])
+            .body(json!({
+                "size": 0,
+                "track_total_hits": true,
+                "query": { "ids": { "values": [id] } }
+            }))
+            .request_timeout(timeout)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status_code());
+
+        let visible = match response {
+            Ok(response) => response.json::<CanarySearchResponse>().await,
+            Err(e) => Err(e),
+        };
+
+        match visible {
+            Ok(visible) if visible.is_visible() => {
+                result.visibility_latency = Some(started.elapsed());
+                return result;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!(
+                    "{} search {} routing {} err {}",
+                    SUBSYSTEM, index, routing, e
+                );
+                return result;
+            }
+        }
+
+        if started.elapsed() >= visibility_timeout {
+            return result;
+        }
+
+        tokio::time::sleep(VISIBILITY_POLL_INTERVAL).await;
+    }
+}
+
+/// Remove canary documents older than retention, cutoff is epoch millis
+/// so indices created before `@timestamp` was mapped as date are cleaned too
+async fn cleanup(
+    client: &Elasticsearch,
+    index: &str,
+    retention: Duration,
+    timeout: Duration,
+) -> Result<(), elasticsearch::Error> {
+    let cutoff = chrono::Utc::now().timestamp_millis() - retention.as_millis() as i64;
+
+    let _ = client
+        .delete_by_query(DeleteByQueryParts::Index(&[index]))
+        .body(json!({
+            "query": {
+                "range": {
+                    "@timestamp": { "lt": cutoff }
+                }
+            }
+        }))
+        .wait_for_completion(false)
+        .request_timeout(timeout)
+        .send()
+        .await?
+        .error_for_status_code()?;
+
+    Ok(())
+}
+
+fn millis(duration: Option<Duration>) -> Value {
+    duration
+        .map(|duration| json!(duration.as_millis() as u64))
+        .unwrap_or(Value::Null)
+}
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/docs-index_.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let options = exporter.options();
+    let timeout = options.timeout_for_subsystem(SUBSYSTEM);
+    let index = options.exporter_canary_index.as_str();
+    let client = exporter.client();
+
+    ensure_index(&client, index, options.exporter_canary_shards, timeout).await?;
+
+    let primaries = client
+        .search_shards(SearchShardsParts::Index(&[index]))
+        .request_timeout(timeout)
+        .send()
+        .await?
+        .json::<SearchShardsResponse>()
+        .await?
+        .primaries();
+
+    let (known, cleaned) = {
+        let mut state = exporter.canary_state().lock().expect("canary state lock");
+        if state.index != index {
+            *state = CanaryState {
+                index: index.to_string(),
+                ..Default::default()
+            };
+        }
+        (state.routings.clone(), state.cleaned)
+    };
+
+    let shards: Vec<u64> = primaries.iter().map(|(shard, _)| *shard).collect();
+    let routings = resolve_routings(&client, index, &shards, known, timeout).await?;
+
+    // Shards are probed concurrently, so poll takes single visibility timeout at most
+    let visibility_timeout = options.exporter_canary_visibility_timeout;
+    let shard_probes = primaries
+        .into_iter()
+        .filter_map(|(shard, name)| {
+            let routing = routings.get(&shard)?.clone();
+            let client = client.clone();
+            let index = index.to_string();
+
+            let probe = tokio::spawn(async move {
+                probe_shard(&client, &index, &routing, timeout, visibility_timeout).await
+            });
+
+            Some((shard, name, probe))
+        })
+        .collect::<Vec<_>>();
+
+    let mut probes: Vec<(u64, String, CanaryProbe)> = Vec::new();
+    for (shard, name, probe) in shard_probes {
+        match probe.await {
+            Ok(result) => probes.push((shard, name, result)),
+            Err(e) => error!("{} probe shard {} err {}", SUBSYSTEM, shard, e),
+        }
+    }
+
+    let retention = options.exporter_canary_retention;
+    let cleanup_due = cleaned
+        .map(|cleaned| cleaned.elapsed() >= retention)
+        .unwrap_or(true);
+    if cleanup_due {
+        if let Err(e) = cleanup(&client, index, retention, timeout).await {
+            error!("{} cleanup {} err {}", SUBSYSTEM, index, e);
+        }
+    }
+
+    let mut state = exporter.canary_state().lock().expect("canary state lock");
+    state.routings = routings;
+    if cleanup_due {
+        state.cleaned = Some(Instant::now());
+    }
+
+    let values = probes
+        .into_iter()
+        .map(|(shard, name, result)| {
+            let write_failures = state.write_failures.entry(shard).or_insert(0);
+            if result.write_latency.is_none() {
+                *write_failures += 1;
+            }
+            let write_failures = *write_failures;
+
+            let visibility_failures = state.visibility_failures.entry(shard).or_insert(0);
+            if result.write_latency.is_some() && result.visibility_latency.is_none() {
+                *visibility_failures += 1;
+            }
+
+            json!({
+                "shard": shard.to_string(),
+                "name": name,
+                "write_latency_millis": millis(result.write_latency),
+                "visibility_latency_millis": millis(result.visibility_latency),
+                "write_failures": write_failures,
+                "visibility_failures": *visibility_failures,
+            })
+        })
+        .collect();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_canary_search_shards() {
+    let shards: SearchShardsResponse =
+        serde_json::from_str(include_str!("../../tests/files/canary_search_shards.json"))
+            .expect("valid json");
+
+    assert_eq!(
+        shards.primaries(),
+        vec![(0, "es-data-2".to_string()), (1, "es-data-1".to_string())]
+    );
+    assert_eq!(shards.routed_shard(), Some(0));
+}
+
+#[tokio::test]
+async fn test_canary_probe() {
+    use elasticsearch::http::transport::Transport;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server};
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // Local stand-in of `_doc` and `_search` endpoints, document becomes
+    // visible on the second search, as if refreshed in between
+    async fn stand_in(
+        request: Request<Body>,
+        searches: Arc<AtomicUsize>,
+        visible_after: usize,
+    ) -> Result<Response<Body>, Infallible> {
+        let path = request.uri().path().to_string();
+
+        let body = match *request.method() {
+            Method::PUT | Method::POST if path.contains("/_doc/") => {
+                json!({ "_index": "canary", "result": "created" })
+            }
+            _ if path.ends_with("/_search") => {
+                let searches = searches.fetch_add(1, Ordering::SeqCst) + 1;
+                let total = if searches >= visible_after { 1 } else { 0 };
+                json!({ "hits": { "total": { "value": total, "relation": "eq" }, "hits": [] } })
+            }
+            _ => {
+                return Ok(Response::builder()
+                    .status(404)
+                    .body(Body::empty())
+                    .expect("valid response"))
+            }
+        };
+
+        Ok(Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .expect("valid response"))
+    }
+
+    async fn serve(visible_after: usize) -> String {
+        let searches = Arc::new(AtomicUsize::new(0));
+
+        let service = make_service_fn(move |_| {
+            let searches = searches.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    stand_in(request, searches.clone(), visible_after)
+                }))
+            }
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+        let url = format!("http://{}", server.local_addr());
+        let _ = tokio::spawn(server);
+
+        url
+    }
+
+    let timeout = Duration::from_secs(5);
+
+    let url = serve(2).await;
+    let client = Elasticsearch::new(Transport::single_node(&url).expect("valid transport"));
+
+    let result = probe_shard(&client, "canary", "0", timeout, Duration::from_secs(5)).await;
+    assert!(result.write_latency.is_some());
+    assert!(result.visibility_latency.is_some());
+    assert!(result.visibility_latency >= Some(VISIBILITY_POLL_INTERVAL));
+
+    // Never visible document is a visibility failure
+    let url = serve(usize::MAX).await;
+    let client = Elasticsearch::new(Transport::single_node(&url).expect("valid transport"));
+
+    let result = probe_shard(&client, "canary", "0", timeout, Duration::from_millis(300)).await;
+    assert!(result.write_latency.is_some());
+    assert!(result.visibility_latency.is_none());
+}
+
+#[tokio::test]
+async fn test_canary_cleanup() {
+    use elasticsearch::http::transport::Transport;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server};
+    use std::convert::Infallible;
+    use std::sync::Arc;
+
+    type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+    // Local stand-in recording request bodies, canary index does not exist
+    async fn stand_in(
+        request: Request<Body>,
+        requests: Requests,
+    ) -> Result<Response<Body>, Infallible> {
+        let status = if request.method() == Method::HEAD {
+            404
+        } else {
+            200
+        };
+        let path = request.uri().path().to_string();
+        let body = hyper::body::to_bytes(request.into_body())
+            .await
+            .expect("request body");
+        requests
+            .lock()
+            .expect("requests lock")
+            .push((path, serde_json::from_slice(&body).unwrap_or(Value::Null)));
+
+        Ok(Response::builder()
+            .status(status)
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"acknowledged":true,"task":"stand-in:1"}"#))
+            .expect("valid response"))
+    }
+
+    let requests = Requests::default();
+    let recorded = requests.clone();
+
+    let service = make_service_fn(move |_| {
+        let recorded = recorded.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                stand_in(request, recorded.clone())
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(service);
+    let url = format!("http://{}", server.local_addr());
+    let _ = tokio::spawn(server);
+
+    let client = Elasticsearch::new(Transport::single_node(&url).expect("valid transport"));
+    let timeout = Duration::from_secs(5);
+    let retention = Duration::from_secs(3600);
+
+    ensure_index(&client, "canary", 1, timeout)
+        .await
+        .expect("canary index");
+    let started = chrono::Utc::now().timestamp_millis();
+    cleanup(&client, "canary", retention, timeout)
+        .await
+        .expect("canary cleanup");
+
+    let requests = requests.lock().expect("requests lock");
+    assert_eq!(requests.len(), 3);
+
+    let (path, create) = &requests[1];
+    assert_eq!(path, "/canary");
+    assert_eq!(
+        create["mappings"]["properties"]["@timestamp"],
+        json!({ "type": "date", "format": "epoch_millis" })
+    );
+
+    let (path, delete) = &requests[2];
+    assert_eq!(path, "/canary/_delete_by_query");
+    let cutoff = delete["query"]["range"]["@timestamp"]["lt"]
+        .as_i64()
+        .expect("numeric cutoff");
+    assert!(cutoff <= started - 3_600_000);
+    assert!(cutoff > started - 3_660_000);
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// Search shards response of canary index
+#[derive(Debug, Deserialize)]
+pub(crate) struct SearchShardsResponse {
+    #[serde(default)]
+    nodes: HashMap<String, SearchShardsNode>,
+    shards: Vec<Vec<SearchShard>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchShardsNode {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchShard {
+    primary: bool,
+    #[serde(default)]
+    node: Option<String>,
+    shard: u64,
+}
+
+impl SearchShardsResponse {
+    /// Shard numbers with name of node holding primary, empty if unassigned
+    pub(crate) fn primaries(&self) -> Vec<(u64, String)> {
+        self.shards
+            .iter()
+            .filter_map(|copies| copies.iter().find(|copy| copy.primary))
+            .map(|primary| {
+                let name = primary
+                    .node
+                    .as_ref()
+                    .and_then(|node| self.nodes.get(node))
+                    .map(|node| node.name.clone())
+                    .unwrap_or_default();
+
+                (primary.shard, name)
+            })
+            .collect()
+    }
+
+    /// Shard number documents with requested routing are routed to
+    pub(crate) fn routed_shard(&self) -> Option<u64> {
+        self.shards
+            .first()
+            .and_then(|copies| copies.first())
+            .map(|copy| copy.shard)
+    }
+}
+
+/// Canary document search response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CanarySearchResponse {
+    hits: CanaryHits,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanaryHits {
+    total: CanaryTotal,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanaryTotal {
+    value: u64,
+}
+
+impl CanarySearchResponse {
+    /// Canary document is visible to search
+    pub(crate) fn is_visible(&self) -> bool {
+        self.hits.total.value > 0
+    }
+}
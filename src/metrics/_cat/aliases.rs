@@ -7,15 +7,18 @@ pub(crate) const SUBSYSTEM: &str = "cat_aliases";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .aliases(CatAliasesParts::Name(&["*"]))
-        .format("json")
-        .h(&["*"])
-        // Return local information, do not retrieve the state from master node (default: false)
-        .local(true)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .aliases(CatAliasesParts::Name(&["*"]))
+                .format("json")
+                .h(&["*"])
+                // Return local information, do not retrieve the state from master node (default: false)
+                .local(true)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     let values = response
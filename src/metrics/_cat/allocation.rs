@@ -5,16 +5,19 @@ pub(crate) const SUBSYSTEM: &str = "cat_allocation";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .allocation(CatAllocationParts::None)
-        .format("json")
-        .h(&["*"])
-        .bytes(Bytes::B)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        // Return local information, do not retrieve the state from master node (default: false)
-        .local(true)
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .allocation(CatAllocationParts::None)
+                .format("json")
+                .h(&["*"])
+                .bytes(Bytes::B)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                // Return local information, do not retrieve the state from master node (default: false)
+                .local(true)
+                .send()
+                .await
+        })
         .await?;
 
     Ok(metric::from_values(response.json::<Vec<Value>>().await?))
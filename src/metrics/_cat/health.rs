@@ -4,14 +4,17 @@ pub(crate) const SUBSYSTEM: &str = "cat_health";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .health()
-        .format("json")
-        .h(&["*"])
-        .time(Time::Ms)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .health()
+                .format("json")
+                .h(&["*"])
+                .time(Time::Ms)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     Ok(metric::from_values(response.json::<Vec<Value>>().await?))
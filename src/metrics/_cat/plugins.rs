@@ -2,15 +2,18 @@ pub(crate) const SUBSYSTEM: &str = "cat_plugins";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .plugins()
-        .format("json")
-        .h(&["*"])
-        // Return local information, do not retrieve the state from master node (default: false)
-        .local(true)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .plugins()
+                .format("json")
+                .h(&["*"])
+                // Return local information, do not retrieve the state from master node (default: false)
+                .local(true)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     Ok(metric::from_values(response.json::<Vec<Value>>().await?))
@@ -5,15 +5,18 @@ pub(crate) const SUBSYSTEM: &str = "cat_recovery";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .recovery(CatRecoveryParts::Index(&["*"]))
-        .format("json")
-        .h(&["*"])
-        .bytes(Bytes::B)
-        .time(Time::Ms)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .recovery(CatRecoveryParts::Index(&["*"]))
+                .format("json")
+                .h(&["*"])
+                .bytes(Bytes::B)
+                .time(Time::Ms)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     Ok(metric::from_values(response.json::<Vec<Value>>().await?))
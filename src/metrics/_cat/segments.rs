@@ -5,14 +5,17 @@ pub(crate) const SUBSYSTEM: &str = "cat_segments";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .segments(CatSegmentsParts::Index(&["*"]))
-        .format("json")
-        .h(&["*"])
-        .bytes(Bytes::B)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .segments(CatSegmentsParts::Index(&["*"]))
+                .format("json")
+                .h(&["*"])
+                .bytes(Bytes::B)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     Ok(metric::from_values(response.json::<Vec<Value>>().await?))
@@ -7,17 +7,20 @@ pub(crate) const SUBSYSTEM: &str = "cat_shards";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .shards(CatShardsParts::Index(&["*"]))
-        .format("json")
-        .h(&["*"])
-        .bytes(Bytes::B)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .time(Time::Ms)
-        // Return local information, do not retrieve the state from master node (default: false)
-        .local(true)
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .shards(CatShardsParts::Index(&["*"]))
+                .format("json")
+                .h(&["*"])
+                .bytes(Bytes::B)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .time(Time::Ms)
+                // Return local information, do not retrieve the state from master node (default: false)
+                .local(true)
+                .send()
+                .await
+        })
         .await?;
 
     let values = response.json::<CatResponse>().await?.into_values(|map| {
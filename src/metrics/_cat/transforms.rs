@@ -5,14 +5,17 @@ pub(crate) const SUBSYSTEM: &str = "cat_transforms";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cat()
-        .transforms(CatTransformsParts::TransformId("*"))
-        .format("json")
-        .h(&["*"])
-        .time(Time::Ms)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cat()
+                .transforms(CatTransformsParts::TransformId("*"))
+                .format("json")
+                .h(&["*"])
+                .time(Time::Ms)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     Ok(metric::from_values(response.json::<Vec<Value>>().await?))
@@ -0,0 +1,3 @@
+mod responses;
+
+pub(crate) mod stats;
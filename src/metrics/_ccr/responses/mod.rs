@@ -0,0 +1,117 @@
+use serde_json::{json, Value};
+
+/// Cross-cluster replication stats response
+#[derive(Debug, Deserialize)]
+pub(crate) struct CcrStatsResponse {
+    auto_follow_stats: AutoFollowStats,
+    follow_stats: FollowStats,
+}
+
+#[derive(Debug, Deserialize)]
+struct AutoFollowStats {
+    number_of_failed_follow_indices: u64,
+    number_of_failed_remote_cluster_state_requests: u64,
+    number_of_successful_follow_indices: u64,
+    #[serde(default)]
+    recent_auto_follow_errors: Vec<Value>,
+    #[serde(default)]
+    auto_followed_clusters: Vec<AutoFollowedCluster>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AutoFollowedCluster {
+    cluster_name: String,
+    time_since_last_check_millis: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FollowStats {
+    #[serde(default)]
+    indices: Vec<FollowIndex>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FollowIndex {
+    index: String,
+    #[serde(default)]
+    total_global_checkpoint_lag: i64,
+    #[serde(default)]
+    shards: Vec<FollowShard>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FollowShard {
+    remote_cluster: String,
+    leader_index: String,
+    follower_index: String,
+    shard_id: u64,
+    leader_global_checkpoint: i64,
+    follower_global_checkpoint: i64,
+    outstanding_read_requests: u64,
+    outstanding_write_requests: u64,
+    write_buffer_operation_count: u64,
+    write_buffer_size_in_bytes: u64,
+    successful_read_requests: u64,
+    failed_read_requests: u64,
+    successful_write_requests: u64,
+    failed_write_requests: u64,
+    time_since_last_read_millis: i64,
+    #[serde(default)]
+    read_exceptions: Vec<Value>,
+    #[serde(default)]
+    fatal_exception: Option<Value>,
+}
+
+impl CcrStatsResponse {
+    /// Follower shards, follower indices and auto-follow values
+    pub(crate) fn into_values(self) -> Vec<Value> {
+        let auto_follow = self.auto_follow_stats;
+
+        let mut values = vec![json!({
+            "auto_follow_failed_follow_indices_count": auto_follow.number_of_failed_follow_indices,
+            "auto_follow_failed_remote_cluster_state_requests":
+                auto_follow.number_of_failed_remote_cluster_state_requests,
+            "auto_follow_successful_follow_indices_count":
+                auto_follow.number_of_successful_follow_indices,
+            "auto_follow_recent_errors": auto_follow.recent_auto_follow_errors.len(),
+        })];
+
+        values.extend(auto_follow.auto_followed_clusters.into_iter().map(|cluster| {
+            json!({
+                "remote_cluster": cluster.cluster_name,
+                "auto_follow_time_since_last_check_millis": cluster.time_since_last_check_millis,
+            })
+        }));
+
+        for index in self.follow_stats.indices {
+            values.push(json!({
+                "follower_index": index.index,
+                "index_global_checkpoint_lag": index.total_global_checkpoint_lag,
+            }));
+
+            values.extend(index.shards.into_iter().map(|shard| {
+                json!({
+                    "remote_cluster": shard.remote_cluster,
+                    "leader_index": shard.leader_index,
+                    "follower_index": shard.follower_index,
+                    "shard": shard.shard_id.to_string(),
+                    "operations_lag":
+                        shard.leader_global_checkpoint - shard.follower_global_checkpoint,
+                    "time_since_last_read_millis": shard.time_since_last_read_millis,
+                    "outstanding_read_requests": shard.outstanding_read_requests,
+                    "outstanding_write_requests": shard.outstanding_write_requests,
+                    "write_buffer_operation_count": shard.write_buffer_operation_count,
+                    "write_buffer_size_in_bytes": shard.write_buffer_size_in_bytes,
+                    "successful_read_requests": shard.successful_read_requests,
+                    "failed_read_requests": shard.failed_read_requests,
+                    "successful_write_requests": shard.successful_write_requests,
+                    "failed_write_requests": shard.failed_write_requests,
+                    "read_exceptions": shard.read_exceptions.len(),
+                    "fatal_exception": shard.fatal_exception.is_some(),
+                })
+            }));
+        }
+
+        values
+    }
+}
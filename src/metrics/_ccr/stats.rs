@@ -0,0 +1,68 @@
+use super::responses::CcrStatsResponse;
+
+pub(crate) const SUBSYSTEM: &str = "ccr";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/ccr-get-stats.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .ccr()
+                .stats()
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response.json::<CcrStatsResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_ccr_stats() {
+    use serde_json::json;
+
+    let stats: CcrStatsResponse =
+        serde_json::from_str(include_str!("../../tests/files/ccr_stats.json")).expect("valid json");
+
+    let values = stats.into_values();
+
+    assert_eq!(
+        values[0],
+        json!({
+            "auto_follow_failed_follow_indices_count": 1,
+            "auto_follow_failed_remote_cluster_state_requests": 0,
+            "auto_follow_successful_follow_indices_count": 2,
+            "auto_follow_recent_errors": 1,
+        })
+    );
+    assert!(values.contains(&json!({
+        "follower_index": "logs-follower",
+        "index_global_checkpoint_lag": 256,
+    })));
+    assert!(values.contains(&json!({
+        "remote_cluster": "leader",
+        "leader_index": "logs",
+        "follower_index": "logs-follower",
+        "shard": "0",
+        "operations_lag": 256,
+        "time_since_last_read_millis": 8,
+        "outstanding_read_requests": 1,
+        "outstanding_write_requests": 1,
+        "write_buffer_operation_count": 64,
+        "write_buffer_size_in_bytes": 1536,
+        "successful_read_requests": 32,
+        "failed_read_requests": 2,
+        "successful_write_requests": 16,
+        "failed_write_requests": 0,
+        "read_exceptions": 1,
+        "fatal_exception": false,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
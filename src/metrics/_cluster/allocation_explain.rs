@@ -0,0 +1,125 @@
+use elasticsearch::cat::CatShardsParts;
+use elasticsearch::cluster::ClusterHealthParts;
+use serde_json::json;
+
+use super::responses::{AllocationExplainResponse, AllocationExplains, CatShard};
+
+pub(crate) const SUBSYSTEM: &str = "allocation_explain";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-allocation-explain.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let timeout = exporter.options().timeout_for_subsystem(SUBSYSTEM);
+    // Cheap health check first, shards are listed only while cluster has unassigned shards
+    let health = exporter
+        .send(|client| async move {
+            client
+                .cluster()
+                .health(ClusterHealthParts::None)
+                .filter_path(&["unassigned_shards"])
+                .request_timeout(timeout)
+                .local(true)
+                .send()
+                .await
+        })
+        .await?
+        .json::<Value>()
+        .await?;
+
+    // Unlabeled gauges never expire, zeroes replace values of the last unassigned shards
+    if health["unassigned_shards"].as_u64().unwrap_or(0) == 0 {
+        return Ok(metric::from_values(
+            AllocationExplains::default().into_values(0),
+        ));
+    }
+
+    let shards: Vec<CatShard> = exporter
+        .send(|client| async move {
+            client
+                .cat()
+                .shards(CatShardsParts::None)
+                .format("json")
+                .h(&["index", "shard", "prirep", "state"])
+                .request_timeout(timeout)
+                .local(true)
+                .send()
+                .await
+        })
+        .await?
+        .json::<Vec<CatShard>>()
+        .await?
+        .into_iter()
+        .filter(|shard| shard.state == "UNASSIGNED")
+        .collect();
+
+    let mut explains = AllocationExplains::default();
+
+    for shard in shards
+        .iter()
+        .take(exporter.options().exporter_allocation_explain_max_shards)
+    {
+        let shard_number = match shard.shard.parse::<u64>() {
+            Ok(number) => number,
+            Err(_) => continue,
+        };
+
+        let response = exporter
+            .send(|client| async move {
+                client
+                    .cluster()
+                    .allocation_explain()
+                    .body(json!({
+                        "index": shard.index,
+                        "shard": shard_number,
+                        "primary": shard.prirep == "p",
+                    }))
+                    .request_timeout(timeout)
+                    .send()
+                    .await
+            })
+            .await?;
+
+        // Shard might get assigned between listing and explaining it
+        match response.error_for_status_code() {
+            Ok(response) => explains
+                .0
+                .push(response.json::<AllocationExplainResponse>().await?),
+            Err(e) => debug!(
+                "{} explain {}[{}] err {}",
+                SUBSYSTEM, shard.index, shard.shard, e
+            ),
+        }
+    }
+
+    Ok(metric::from_values(explains.into_values(shards.len())))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_allocation_explain() {
+    let explain: AllocationExplainResponse = serde_json::from_str(include_str!(
+        "../../tests/files/cluster_allocation_explain.json"
+    ))
+    .expect("valid json");
+
+    let values = AllocationExplains(vec![explain]).into_values(3);
+
+    assert_eq!(
+        values,
+        vec![
+            json!({"unassigned_shards": 3, "sampled_shards": 1}),
+            json!({"reason": "NODE_LEFT", "reason_shards": 1}),
+            json!({"can_allocate": "no", "can_allocate_shards": 1}),
+            json!({"decider": "disk_threshold", "decider_shards": 1}),
+            json!({"decider": "same_shard", "decider_shards": 1}),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+
+    assert_eq!(
+        AllocationExplains::default().into_values(0),
+        vec![json!({"unassigned_shards": 0, "sampled_shards": 0})]
+    );
+}
@@ -1,2 +1,9 @@
+mod responses;
+
+pub(crate) mod allocation_explain;
 pub(crate) mod health;
+pub(crate) mod pending_tasks;
+pub(crate) mod remote_info;
+pub(crate) mod settings;
+pub(crate) mod state;
 pub(crate) mod stats;
@@ -0,0 +1,65 @@
+use super::responses::PendingTasksResponse;
+
+pub(crate) const SUBSYSTEM: &str = "cluster_pending_tasks";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-pending.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .cluster()
+                .pending_tasks()
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response.json::<PendingTasksResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_cluster_pending_tasks() {
+    use serde_json::json;
+
+    let tasks: PendingTasksResponse =
+        serde_json::from_str(include_str!("../../tests/files/cluster_pending_tasks.json"))
+            .expect("valid json");
+
+    let values = tasks.into_values();
+
+    assert_eq!(values.len(), 4);
+    assert_eq!(
+        values[0],
+        json!({"tasks": 5, "oldest_time_in_queue_millis": 86843})
+    );
+    // Task arguments such as index names are not part of source label
+    assert!(values.contains(&json!({
+        "priority": "HIGH",
+        "source": "put-mapping",
+        "queue": 3,
+        "time_in_queue_millis": 86843,
+        "executing": true,
+    })));
+    assert!(values.contains(&json!({
+        "priority": "URGENT",
+        "source": "shard-started",
+        "queue": 1,
+        "time_in_queue_millis": 842,
+        "executing": false,
+    })));
+    assert!(values.contains(&json!({
+        "priority": "NORMAL",
+        "source": "cluster_reroute",
+        "queue": 1,
+        "time_in_queue_millis": 120,
+        "executing": false,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
@@ -0,0 +1,59 @@
+use super::responses::RemoteInfoResponse;
+
+pub(crate) const SUBSYSTEM: &str = "remote_info";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-remote-info.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .cluster()
+                .remote_info()
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response.json::<RemoteInfoResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_remote_info() {
+    use serde_json::json;
+
+    let remote_info: RemoteInfoResponse =
+        serde_json::from_str(include_str!("../../tests/files/remote_info.json"))
+            .expect("valid json");
+
+    let values = remote_info.into_values();
+
+    assert_eq!(
+        values,
+        vec![
+            json!({
+                "remote": "dr",
+                "mode": "proxy",
+                "connected": false,
+                "skip_unavailable": true,
+                "num_proxy_sockets_connected": 0,
+                "max_proxy_socket_connections": 18,
+            }),
+            json!({
+                "remote": "leader",
+                "mode": "sniff",
+                "connected": true,
+                "skip_unavailable": false,
+                "num_nodes_connected": 3,
+                "max_connections_per_cluster": 3,
+            }),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
@@ -0,0 +1,618 @@
+use serde_json::{json, Map, Value};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Label key of plain string arrays, keyed by flattened array path
+/// e.g.: "nodes": {"versions": ["7.9.3", "7.7.0"]}
+const STRING_ARRAY_LABELS: &[(&str, &str)] = &[("nodes_versions", "version")];
+
+/// Fallback label key of plain string arrays
+const DEFAULT_ARRAY_LABEL: &str = "name";
+
+/// Cluster stats response
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClusterStatsResponse(Value);
+
+impl ClusterStatsResponse {
+    /// Split arrays such as `nodes.versions`, `nodes.os.names` or `nodes.plugins`
+    /// out of response into separate labeled values, remaining response is
+    /// returned as the first value
+    pub(crate) fn into_values(mut self) -> Vec<Value> {
+        let mut values: Vec<Value> = Vec::new();
+
+        extract_arrays("", &mut self.0, &mut values);
+
+        values.insert(0, self.0);
+
+        values
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}_{}", prefix, key)
+    }
+}
+
+fn extract_arrays(prefix: &str, value: &mut Value, output: &mut Vec<Value>) {
+    if let Some(map) = value.as_object_mut() {
+        let array_keys = map
+            .iter()
+            .filter(|(_, value)| value.is_array())
+            .map(|(key, _)| key.to_string())
+            .collect::<Vec<String>>();
+
+        for key in array_keys {
+            if let Some(Value::Array(array)) = map.remove(&key) {
+                let path = join_key(prefix, &key);
+
+                output.extend(
+                    array
+                        .into_iter()
+                        .filter_map(|item| into_labeled_value(&path, item)),
+                );
+            }
+        }
+
+        for (key, object_value) in map.iter_mut() {
+            extract_arrays(&join_key(prefix, key), object_value, output);
+        }
+    }
+}
+
+// Array item is flattened into single level map, where string values become
+// labels and numbers are prefixed by array path, e.g.:
+//
+// "os": {"names": [{"count": 263, "name": "Linux"}]}
+//
+// becomes
+//
+// {"nodes_os_names_count": 263, "name": "Linux"}
+//
+// items without "count" such as plugins get "_info" metric
+fn into_labeled_value(path: &str, item: Value) -> Option<Value> {
+    let mut labeled = Map::new();
+
+    match item {
+        Value::String(label) => {
+            let label_key = STRING_ARRAY_LABELS
+                .iter()
+                .find(|(array_path, _)| *array_path == path)
+                .map(|(_, label_key)| *label_key)
+                .unwrap_or(DEFAULT_ARRAY_LABEL);
+
+            let _ = labeled.insert(label_key.into(), Value::String(label));
+            let _ = labeled.insert(join_key(path, "count"), Value::from(1));
+        }
+        Value::Object(map) => {
+            if !map.contains_key("count") {
+                let _ = labeled.insert(join_key(path, "info"), Value::from(1));
+            }
+
+            for (key, value) in map.into_iter() {
+                match value {
+                    Value::String(_) => {
+                        let _ = labeled.insert(key, value);
+                    }
+                    Value::Number(_) | Value::Bool(_) => {
+                        let _ = labeled.insert(join_key(path, &key), value);
+                    }
+                    // Nested values, e.g.: "extended_plugins": [] are skipped
+                    _ => {}
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(Value::Object(labeled))
+}
+
+/// Cluster pending tasks response
+#[derive(Debug, Deserialize)]
+pub(crate) struct PendingTasksResponse {
+    tasks: Vec<PendingTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PendingTask {
+    priority: String,
+    source: String,
+    #[serde(default)]
+    executing: bool,
+    #[serde(default)]
+    time_in_queue_millis: u64,
+}
+
+/// Pending tasks of the same priority and source
+#[derive(Debug, Default)]
+struct PendingTasksGroup {
+    queue: u64,
+    time_in_queue_millis: u64,
+    executing: bool,
+}
+
+/// Source of pending task without task arguments, e.g.:
+/// "put-mapping [index/uuid]" becomes "put-mapping"
+fn task_source(source: &str) -> &str {
+    source
+        .split(|c: char| c.is_whitespace() || c == '[' || c == '(' || c == '{')
+        .next()
+        .filter(|source| !source.is_empty())
+        .unwrap_or("unknown")
+}
+
+impl PendingTasksResponse {
+    /// Aggregate pending tasks by priority and source, so that metrics
+    /// cardinality does not grow with queue length. First value holds
+    /// totals of whole queue
+    pub(crate) fn into_values(self) -> Vec<Value> {
+        let mut groups: BTreeMap<(String, String), PendingTasksGroup> = BTreeMap::new();
+
+        let mut oldest_time_in_queue_millis = 0;
+
+        for task in self.tasks.iter() {
+            oldest_time_in_queue_millis =
+                cmp::max(oldest_time_in_queue_millis, task.time_in_queue_millis);
+
+            let group = groups
+                .entry((task.priority.clone(), task_source(&task.source).to_string()))
+                .or_default();
+
+            group.queue += 1;
+            group.time_in_queue_millis =
+                cmp::max(group.time_in_queue_millis, task.time_in_queue_millis);
+            group.executing |= task.executing;
+        }
+
+        let mut values = vec![json!({
+            "tasks": self.tasks.len(),
+            "oldest_time_in_queue_millis": oldest_time_in_queue_millis,
+        })];
+
+        values.extend(groups.into_iter().map(|((priority, source), group)| {
+            json!({
+                "priority": priority,
+                "source": source,
+                "queue": group.queue,
+                "time_in_queue_millis": group.time_in_queue_millis,
+                "executing": group.executing,
+            })
+        }));
+
+        values
+    }
+}
+
+/// Cluster state response, limited by filter_path to summarized fields
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClusterStateResponse {
+    #[serde(default)]
+    version: u64,
+    #[serde(default)]
+    master_node: Option<String>,
+    #[serde(default)]
+    nodes: HashMap<String, StateNode>,
+    #[serde(default)]
+    metadata: StateMetadata,
+    #[serde(default)]
+    routing_table: StateRoutingTable,
+    #[serde(default)]
+    routing_nodes: StateRoutingNodes,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateNode {
+    name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StateMetadata {
+    #[serde(default)]
+    indices: HashMap<String, StateIndexMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateIndexMetadata {
+    state: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StateRoutingTable {
+    #[serde(default)]
+    indices: HashMap<String, StateIndexRouting>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateIndexRouting {
+    #[serde(default)]
+    shards: HashMap<String, Vec<StateShard>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StateRoutingNodes {
+    #[serde(default)]
+    unassigned: Vec<StateShard>,
+    #[serde(default)]
+    nodes: HashMap<String, Vec<StateShard>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateShard {
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    primary: bool,
+    #[serde(default)]
+    unassigned_info: Option<StateUnassignedInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateUnassignedInfo {
+    reason: String,
+}
+
+fn count_by<I: Iterator<Item = String>>(keys: I) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+
+    for key in keys {
+        *counts.entry(key).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+impl ClusterStateResponse {
+    /// Summarize cluster state into aggregates, series count depends on
+    /// number of nodes rather than number of shards
+    pub(crate) fn into_values(self) -> Vec<Value> {
+        let node_name = |node_id: &str| {
+            self.nodes
+                .get(node_id)
+                .map(|node| node.name.clone())
+                .unwrap_or_else(|| node_id.to_string())
+        };
+
+        let mut values = vec![json!({ "version": self.version })];
+
+        if let Some(ref master_node) = self.master_node {
+            values.push(json!({ "master_node": node_name(master_node), "master_info": 1 }));
+        }
+
+        for (node_id, shards) in self.routing_nodes.nodes.iter() {
+            let prireps = count_by(
+                shards
+                    .iter()
+                    .map(|shard| if shard.primary { "p" } else { "r" }.to_string()),
+            );
+
+            values.extend(prireps.into_iter().map(|(prirep, count)| {
+                json!({ "name": node_name(node_id), "prirep": prirep, "node_shards": count })
+            }));
+        }
+
+        let reasons = count_by(self.routing_nodes.unassigned.iter().map(|shard| {
+            shard
+                .unassigned_info
+                .as_ref()
+                .map(|info| info.reason.clone())
+                .unwrap_or_else(|| "UNKNOWN".into())
+        }));
+        values.extend(
+            reasons
+                .into_iter()
+                .map(|(reason, count)| json!({ "reason": reason, "unassigned_shards": count })),
+        );
+
+        // Unlike /_cat/shards RELOCATING shards are kept
+        let shard_states = count_by(
+            self.routing_table
+                .indices
+                .values()
+                .flat_map(|index| index.shards.values())
+                .flatten()
+                .filter_map(|shard| shard.state.clone()),
+        );
+        values.extend(
+            shard_states
+                .into_iter()
+                .map(|(state, count)| json!({ "state": state, "shards": count })),
+        );
+
+        let index_states = count_by(
+            self.metadata
+                .indices
+                .values()
+                .map(|index| index.state.clone()),
+        );
+        values.extend(
+            index_states
+                .into_iter()
+                .map(|(state, count)| json!({ "state": state, "indices_count": count })),
+        );
+
+        values
+    }
+}
+
+/// Unassigned shard from /_cat/shards
+#[derive(Debug, Deserialize)]
+pub(crate) struct CatShard {
+    pub(crate) index: String,
+    pub(crate) shard: String,
+    pub(crate) prirep: String,
+    pub(crate) state: String,
+}
+
+/// Cluster allocation explain response of single shard
+#[derive(Debug, Deserialize)]
+pub(crate) struct AllocationExplainResponse {
+    #[serde(default)]
+    unassigned_info: Option<ExplainUnassignedInfo>,
+    #[serde(default)]
+    can_allocate: Option<String>,
+    #[serde(default)]
+    node_allocation_decisions: Vec<ExplainNodeDecision>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplainUnassignedInfo {
+    reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplainNodeDecision {
+    #[serde(default)]
+    deciders: Vec<ExplainDecider>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplainDecider {
+    decider: String,
+    decision: String,
+}
+
+/// Allocation explanations of sampled unassigned shards
+#[derive(Debug, Default)]
+pub(crate) struct AllocationExplains(pub(crate) Vec<AllocationExplainResponse>);
+
+impl AllocationExplains {
+    /// Count sampled shards by unassigned reason, allocation decision and
+    /// deciders returning NO on any of the nodes
+    pub(crate) fn into_values(self, unassigned_shards: usize) -> Vec<Value> {
+        let mut reasons: BTreeMap<String, u64> = BTreeMap::new();
+        let mut decisions: BTreeMap<String, u64> = BTreeMap::new();
+        let mut deciders: BTreeMap<String, u64> = BTreeMap::new();
+
+        let sampled_shards = self.0.len();
+
+        for explain in self.0 {
+            if let Some(info) = explain.unassigned_info {
+                *reasons.entry(info.reason).or_insert(0) += 1;
+            }
+
+            if let Some(can_allocate) = explain.can_allocate {
+                *decisions.entry(can_allocate).or_insert(0) += 1;
+            }
+
+            // Decider is counted once per shard even if it says NO on every node
+            let mut shard_deciders: Vec<String> = explain
+                .node_allocation_decisions
+                .into_iter()
+                .flat_map(|node| node.deciders)
+                .filter(|decider| decider.decision == "NO")
+                .map(|decider| decider.decider)
+                .collect();
+            shard_deciders.sort();
+            shard_deciders.dedup();
+
+            for decider in shard_deciders {
+                *deciders.entry(decider).or_insert(0) += 1;
+            }
+        }
+
+        let mut values = vec![json!({
+            "unassigned_shards": unassigned_shards,
+            "sampled_shards": sampled_shards,
+        })];
+
+        values.extend(
+            reasons
+                .into_iter()
+                .map(|(reason, count)| json!({ "reason": reason, "reason_shards": count })),
+        );
+
+        values.extend(decisions.into_iter().map(|(can_allocate, count)| {
+            json!({ "can_allocate": can_allocate, "can_allocate_shards": count })
+        }));
+
+        values.extend(
+            deciders
+                .into_iter()
+                .map(|(decider, count)| json!({ "decider": decider, "decider_shards": count })),
+        );
+
+        values
+    }
+}
+
+/// Remote cluster connections keyed by alias
+#[derive(Debug, Deserialize)]
+pub(crate) struct RemoteInfoResponse(BTreeMap<String, Map<String, Value>>);
+
+impl RemoteInfoResponse {
+    /// Numeric and boolean connection fields of each remote labeled by alias
+    /// and mode, seeds and proxy addresses are skipped
+    pub(crate) fn into_values(self) -> Vec<Value> {
+        self.0
+            .into_iter()
+            .map(|(remote, info)| {
+                // Mode is missing before 7.6, where only sniff mode was available
+                let mode = info
+                    .get("mode")
+                    .cloned()
+                    .unwrap_or_else(|| Value::String("sniff".into()));
+
+                let mut value: Map<String, Value> = info
+                    .into_iter()
+                    .filter(|(_, field)| field.is_number() || field.is_boolean())
+                    .collect();
+
+                let _ = value.insert("remote".into(), Value::String(remote));
+                let _ = value.insert("mode".into(), mode);
+
+                Value::Object(value)
+            })
+            .collect()
+    }
+}
+
+/// Flat cluster settings response including defaults
+#[derive(Debug, Deserialize)]
+pub(crate) struct ClusterSettingsResponse {
+    #[serde(default)]
+    transient: Map<String, Value>,
+    #[serde(default)]
+    persistent: Map<String, Value>,
+    #[serde(default)]
+    defaults: Map<String, Value>,
+}
+
+/// Setting key matches exactly or by trailing wildcard, e.g.: cluster.blocks.*
+fn setting_allowed(allowlist: &[String], key: &str) -> bool {
+    allowlist
+        .iter()
+        .any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => allowed == key,
+        })
+}
+
+/// Elasticsearch byte size value, units are powers of 1024, e.g.: 500mb
+fn setting_bytes(value: &str) -> Option<u64> {
+    let number_end = value.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = value.split_at(number_end);
+
+    let multiplier: u64 = match unit {
+        "b" => 1,
+        "kb" => 1 << 10,
+        "mb" => 1 << 20,
+        "gb" => 1 << 30,
+        "tb" => 1 << 40,
+        "pb" => 1 << 50,
+        _ => return None,
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|number| (number * multiplier as f64) as u64)
+}
+
+/// States of enum-like settings exported as inactive while not set,
+/// so that series of a state become 0 before the state is ever left
+fn known_states(setting: &str) -> &'static [&'static str] {
+    match setting {
+        "cluster.routing.allocation.enable" => &["all", "primaries", "new_primaries", "none"],
+        "cluster.routing.rebalance.enable" => &["all", "primaries", "replicas", "none"],
+        "cluster.routing.allocation.allow_rebalance" => {
+            &["always", "indices_primaries_active", "indices_all_active"]
+        }
+        _ => &[],
+    }
+}
+
+/// Setting value typed by its notation, enum-like settings are switches
+/// labeled by state, e.g.: cluster.routing.allocation.enable=none
+fn setting_value(setting: &str, value: &str) -> Map<String, Value> {
+    let mut map = Map::new();
+
+    let (key, field) = if let Ok(switch) = value.parse::<bool>() {
+        ("enabled", json!(switch))
+    } else if let Ok(number) = value.parse::<i64>() {
+        ("value", json!(number))
+    } else if let Ok(number) = value.parse::<f64>() {
+        ("value", json!(number))
+    } else if let Some(percent) = value
+        .strip_suffix('%')
+        .and_then(|percent| percent.parse::<f64>().ok())
+    {
+        ("percent", json!(percent))
+    } else if let Some(bytes) = setting_bytes(value) {
+        ("bytes", json!(bytes))
+    } else if let Ok(duration) = humantime::parse_duration(value) {
+        ("duration_millis", json!(duration.as_millis() as u64))
+    } else {
+        let _ = map.insert("state".into(), json!(value));
+        ("active", json!(true))
+    };
+
+    let _ = map.insert("setting".into(), json!(setting));
+    let _ = map.insert(key.into(), field);
+    map
+}
+
+impl ClusterSettingsResponse {
+    /// Effective value of allowed settings labeled by source, transient
+    /// settings take precedence over persistent and defaults. Known and
+    /// previously seen states of enum-like settings are exported as inactive
+    pub(crate) fn into_values(
+        self,
+        allowlist: &[String],
+        seen_states: &mut BTreeMap<String, BTreeSet<String>>,
+    ) -> Vec<Value> {
+        let mut settings: BTreeMap<String, (&'static str, String)> = BTreeMap::new();
+
+        for (source, values) in [
+            ("defaults", self.defaults),
+            ("persistent", self.persistent),
+            ("transient", self.transient),
+        ] {
+            for (setting, value) in values {
+                if !setting_allowed(allowlist, &setting) {
+                    continue;
+                }
+
+                let value = match value {
+                    Value::String(value) => value,
+                    Value::Number(number) => number.to_string(),
+                    Value::Bool(switch) => switch.to_string(),
+                    // Lists and nested settings are not exported
+                    _ => continue,
+                };
+
+                let _ = settings.insert(setting, (source, value));
+            }
+        }
+
+        let mut values = Vec::new();
+
+        for (setting, (source, value)) in settings {
+            let mut map = setting_value(&setting, &value);
+            let _ = map.insert("source".into(), json!(source));
+
+            let state = map.get("state").and_then(Value::as_str).map(String::from);
+            values.push(Value::Object(map));
+
+            // Active state goes first, so its metric is registered before zeroes
+            if let Some(state) = state {
+                let states = seen_states.entry(setting.clone()).or_default();
+                states.extend(known_states(&setting).iter().map(|state| state.to_string()));
+                let _ = states.insert(state.clone());
+
+                for inactive in states.iter().filter(|inactive| **inactive != state) {
+                    values.push(json!({
+                        "setting": setting,
+                        "source": source,
+                        "state": inactive,
+                        "active": false,
+                    }));
+                }
+            }
+        }
+
+        values
+    }
+}
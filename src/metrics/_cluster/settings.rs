@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+use super::responses::ClusterSettingsResponse;
+
+pub(crate) const SUBSYSTEM: &str = "cluster_settings";
+
+/// States of enum-like settings seen since exporter start, keyed by setting
+pub(crate) type SeenStates = Mutex<BTreeMap<String, BTreeSet<String>>>;
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-get-settings.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .cluster()
+                .get_settings()
+                .include_defaults(true)
+                .flat_settings(true)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response
+        .json::<ClusterSettingsResponse>()
+        .await?
+        .into_values(
+            &exporter.options().exporter_cluster_settings,
+            &mut exporter
+                .cluster_settings_states()
+                .lock()
+                .expect("cluster settings states lock"),
+        );
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_cluster_settings() {
+    use serde_json::json;
+
+    let settings: ClusterSettingsResponse =
+        serde_json::from_str(include_str!("../../tests/files/cluster_settings.json"))
+            .expect("valid json");
+
+    let allowlist: Vec<String> = vec![
+        "cluster.routing.allocation.enable".into(),
+        "cluster.routing.allocation.disk.*".into(),
+        "cluster.blocks.*".into(),
+        "cluster.max_shards_per_node".into(),
+        "indices.recovery.max_bytes_per_sec".into(),
+    ];
+
+    let mut seen_states = BTreeMap::new();
+    let _ = seen_states.insert(
+        "cluster.routing.allocation.enable".to_string(),
+        vec!["custom".to_string()].into_iter().collect(),
+    );
+
+    let values = settings.into_values(&allowlist, &mut seen_states);
+
+    assert_eq!(
+        values,
+        vec![
+            json!({ "setting": "cluster.blocks.read_only", "source": "defaults", "enabled": false }),
+            json!({ "setting": "cluster.blocks.read_only_allow_delete", "source": "persistent", "enabled": true }),
+            json!({ "setting": "cluster.max_shards_per_node", "source": "persistent", "value": 3000 }),
+            json!({ "setting": "cluster.routing.allocation.disk.reroute_interval", "source": "defaults", "duration_millis": 60000 }),
+            json!({ "setting": "cluster.routing.allocation.disk.threshold_enabled", "source": "defaults", "enabled": true }),
+            json!({ "setting": "cluster.routing.allocation.disk.watermark.flood_stage", "source": "persistent", "bytes": 524288000 }),
+            json!({ "setting": "cluster.routing.allocation.disk.watermark.high", "source": "transient", "percent": 92.5 }),
+            json!({ "setting": "cluster.routing.allocation.disk.watermark.low", "source": "defaults", "percent": 85.0 }),
+            json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "none", "active": true }),
+            json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "all", "active": false }),
+            json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "custom", "active": false }),
+            json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "new_primaries", "active": false }),
+            json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "primaries", "active": false }),
+            json!({ "setting": "indices.recovery.max_bytes_per_sec", "source": "defaults", "bytes": 41943040 }),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+
+    // Allocation re-enabled, previously active state is exported as inactive
+    let settings: ClusterSettingsResponse = serde_json::from_value(json!({
+        "transient": { "cluster.routing.allocation.enable": "all" }
+    }))
+    .expect("valid json");
+    let values = settings.into_values(&allowlist, &mut seen_states);
+    assert!(values.contains(&json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "all", "active": true })));
+    assert!(values.contains(&json!({ "setting": "cluster.routing.allocation.enable", "source": "transient", "state": "none", "active": false })));
+}
@@ -0,0 +1,75 @@
+use elasticsearch::cluster::ClusterStateParts;
+
+use super::responses::ClusterStateResponse;
+
+pub(crate) const SUBSYSTEM: &str = "cluster_state";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-state.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .cluster()
+                .state(ClusterStateParts::Metric(&[
+                    "version",
+                    "master_node",
+                    "nodes",
+                    "metadata",
+                    "routing_table",
+                    "routing_nodes",
+                ]))
+                // Full cluster state is huge, fetch only summarized fields
+                .filter_path(&[
+                    "version",
+                    "master_node",
+                    "nodes.*.name",
+                    "metadata.indices.*.state",
+                    "routing_table.indices.*.shards.*.state",
+                    "routing_nodes.unassigned.unassigned_info.reason",
+                    "routing_nodes.nodes.*.primary",
+                ])
+                // Return local information, do not retrieve the state from master node (default: false)
+                .local(true)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response.json::<ClusterStateResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_cluster_state() {
+    use serde_json::json;
+
+    let state: ClusterStateResponse =
+        serde_json::from_str(include_str!("../../tests/files/cluster_state.json"))
+            .expect("valid json");
+
+    let values = state.into_values();
+
+    assert_eq!(values[0], json!({"version": 5621}));
+    assert!(values.contains(&json!({"master_node": "m1-master-1", "master_info": 1})));
+
+    assert!(values.contains(&json!({"name": "m1-data-1", "prirep": "p", "node_shards": 2})));
+    assert!(values.contains(&json!({"name": "m1-data-1", "prirep": "r", "node_shards": 1})));
+    assert!(values.contains(&json!({"name": "m1-data-2", "prirep": "r", "node_shards": 1})));
+
+    assert!(values.contains(&json!({"reason": "NODE_LEFT", "unassigned_shards": 2})));
+    assert!(values.contains(&json!({"reason": "INDEX_CREATED", "unassigned_shards": 1})));
+
+    assert!(values.contains(&json!({"state": "STARTED", "shards": 3})));
+    assert!(values.contains(&json!({"state": "RELOCATING", "shards": 1})));
+    assert!(values.contains(&json!({"state": "UNASSIGNED", "shards": 3})));
+
+    assert!(values.contains(&json!({"state": "open", "indices_count": 2})));
+    assert!(values.contains(&json!({"state": "close", "indices_count": 1})));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
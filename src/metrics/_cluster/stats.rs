@@ -1,17 +1,56 @@
 use elasticsearch::cluster::ClusterStatsParts;
 
+use super::responses::ClusterStatsResponse;
+
 pub(crate) const SUBSYSTEM: &str = "cluster_stats";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .cluster()
-        .stats(ClusterStatsParts::None)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .cluster()
+                .stats(ClusterStatsParts::None)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
-    Ok(metric::from_value(response.json::<Value>().await?))
+    let values = response.json::<ClusterStatsResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
 }
 
 crate::poll_metrics!();
+
+#[test]
+fn test_cluster_stats() {
+    use serde_json::json;
+
+    let stats: ClusterStatsResponse =
+        serde_json::from_str(include_str!("../../tests/files/cluster_stats.json"))
+            .expect("valid json");
+
+    let values = stats.into_values();
+
+    // Arrays are removed from response
+    assert!(values[0]["nodes"]["versions"].is_null());
+    assert!(values[0]["nodes"]["os"]["names"].is_null());
+    assert!(values[0]["nodes"]["plugins"].is_null());
+    assert_eq!(values[0]["nodes"]["count"]["data"], 236);
+
+    assert!(values.contains(&json!({"nodes_versions_count": 1, "version": "7.9.3"})));
+    assert!(values.contains(&json!({"nodes_versions_count": 1, "version": "7.7.0"})));
+    assert!(values.contains(&json!({"nodes_os_names_count": 263, "name": "Linux"})));
+
+    let plugin = values
+        .iter()
+        .find(|value| value["version"] == "7.9.3" && value.get("nodes_plugins_info").is_some())
+        .expect("plugin value");
+    assert_eq!(plugin["name"], "analysis-stempel");
+    assert_eq!(plugin["nodes_plugins_has_native_controller"], false);
+    assert!(plugin.get("extended_plugins").is_none());
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
@@ -0,0 +1,88 @@
+use serde_json::{json, Value};
+use std::cmp;
+use std::collections::HashMap;
+
+/// Data stream health colors, exported as switch per color
+const COLORS: &[&str] = &["green", "yellow", "red"];
+
+/// Data streams stats response
+#[derive(Debug, Deserialize)]
+pub(crate) struct DataStreamsStatsResponse {
+    #[serde(default)]
+    data_streams: Vec<DataStreamStats>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataStreamStats {
+    data_stream: String,
+    backing_indices: u64,
+    store_size_bytes: u64,
+    maximum_timestamp: i64,
+}
+
+/// Get data streams response
+#[derive(Debug, Deserialize)]
+pub(crate) struct DataStreamsResponse {
+    #[serde(default)]
+    data_streams: Vec<DataStream>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataStream {
+    name: String,
+    #[serde(default)]
+    generation: u64,
+    status: String,
+    #[serde(default)]
+    ilm_policy: Option<String>,
+}
+
+impl DataStreamsResponse {
+    /// Merge data streams with their stats, maximum timestamp is exported as
+    /// age of the latest document
+    pub(crate) fn into_values(
+        self,
+        stats: DataStreamsStatsResponse,
+        now_millis: i64,
+    ) -> Vec<Value> {
+        let mut stats: HashMap<String, DataStreamStats> = stats
+            .data_streams
+            .into_iter()
+            .map(|stats| (stats.data_stream.clone(), stats))
+            .collect();
+
+        let mut values: Vec<Value> = Vec::new();
+
+        for data_stream in self.data_streams {
+            let status = data_stream.status.to_lowercase();
+
+            values.extend(COLORS.iter().map(|color| {
+                json!({
+                    "data_stream": data_stream.name,
+                    "color": color,
+                    "health": *color == status,
+                })
+            }));
+
+            let mut value = json!({
+                "data_stream": data_stream.name,
+                "ilm_policy": data_stream.ilm_policy.unwrap_or_default(),
+                "generation": data_stream.generation,
+            });
+
+            if let Some(stats) = stats.remove(&data_stream.name) {
+                value["backing_indices_count"] = json!(stats.backing_indices);
+                value["store_size_bytes"] = json!(stats.store_size_bytes);
+                // Empty data stream reports 0 maximum timestamp
+                if stats.maximum_timestamp > 0 {
+                    value["maximum_timestamp_age_millis"] =
+                        json!(cmp::max(now_millis - stats.maximum_timestamp, 0));
+                }
+            }
+
+            values.push(value);
+        }
+
+        values
+    }
+}
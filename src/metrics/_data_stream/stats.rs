@@ -0,0 +1,92 @@
+use elasticsearch::indices::{IndicesDataStreamsStatsParts, IndicesGetDataStreamParts};
+
+use super::responses::{DataStreamsResponse, DataStreamsStatsResponse};
+
+pub(crate) const SUBSYSTEM: &str = "data_streams";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/data-stream-stats-api.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let timeout = exporter.options().timeout_for_subsystem(SUBSYSTEM);
+
+    let stats = exporter
+        .send(|client| async move {
+            client
+                .indices()
+                .data_streams_stats(IndicesDataStreamsStatsParts::None)
+                .request_timeout(timeout)
+                .send()
+                .await
+        })
+        .await?
+        .json::<DataStreamsStatsResponse>()
+        .await?;
+
+    let values = exporter
+        .send(|client| async move {
+            client
+                .indices()
+                .get_data_stream(IndicesGetDataStreamParts::None)
+                .request_timeout(timeout)
+                .send()
+                .await
+        })
+        .await?
+        .json::<DataStreamsResponse>()
+        .await?
+        .into_values(stats, chrono::Utc::now().timestamp_millis());
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_data_streams() {
+    use serde_json::json;
+
+    let stats: DataStreamsStatsResponse =
+        serde_json::from_str(include_str!("../../tests/files/data_streams_stats.json"))
+            .expect("valid json");
+    let data_streams: DataStreamsResponse =
+        serde_json::from_str(include_str!("../../tests/files/data_streams.json"))
+            .expect("valid json");
+
+    // 2021-08-21T12:00:00Z
+    let values = data_streams.into_values(stats, 1_629_547_200_000);
+
+    assert!(values.contains(&json!({
+        "data_stream": "logs-nginx-default",
+        "color": "yellow",
+        "health": true,
+    })));
+    assert!(values.contains(&json!({
+        "data_stream": "logs-nginx-default",
+        "color": "green",
+        "health": false,
+    })));
+    assert!(values.contains(&json!({
+        "data_stream": "logs-nginx-default",
+        "ilm_policy": "logs",
+        "generation": 3,
+        "backing_indices_count": 3,
+        "store_size_bytes": 3_670_016,
+        "maximum_timestamp_age_millis": 60_000,
+    })));
+    // Data stream without ILM policy and stats
+    assert!(values.contains(&json!({
+        "data_stream": "metrics-app-default",
+        "ilm_policy": "",
+        "generation": 1,
+    })));
+    // Empty data stream has no maximum timestamp age
+    assert!(values.contains(&json!({
+        "data_stream": "traces-app-default",
+        "ilm_policy": "traces",
+        "generation": 1,
+        "backing_indices_count": 1,
+        "store_size_bytes": 225,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
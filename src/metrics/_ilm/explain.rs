@@ -0,0 +1,71 @@
+use elasticsearch::ilm::IlmExplainLifecycleParts;
+
+use super::responses::IlmExplainResponse;
+
+pub(crate) const SUBSYSTEM: &str = "ilm";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/ilm-explain-lifecycle.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .ilm()
+                .explain_lifecycle(IlmExplainLifecycleParts::Index("*"))
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response
+        .json::<IlmExplainResponse>()
+        .await?
+        .into_values(chrono::Utc::now().timestamp_millis());
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_ilm() {
+    use serde_json::json;
+
+    let explain: IlmExplainResponse =
+        serde_json::from_str(include_str!("../../tests/files/ilm_explain.json"))
+            .expect("valid json");
+
+    // 2021-08-21T12:00:00Z
+    let values = explain.into_values(1_629_547_200_000);
+
+    assert_eq!(
+        values[0],
+        json!({"managed_indices_count": 3, "unmanaged_indices_count": 1})
+    );
+    assert!(values.contains(&json!({
+        "policy": "logs",
+        "phase": "hot",
+        "step": "check-rollover-ready",
+        "indices_count": 1,
+    })));
+    assert!(values.contains(&json!({
+        "policy": "logs",
+        "phase": "hot",
+        "step": "ERROR",
+        "indices_count": 1,
+    })));
+    assert!(values.contains(&json!({
+        "policy": "logs",
+        "phase": "hot",
+        "oldest_index_age_millis": 172_800_000,
+        "time_in_phase_max_millis": 172_800_000,
+    })));
+    assert!(values.contains(&json!({
+        "policy": "logs",
+        "failed_step": "attempt-rollover",
+        "error_indices_count": 1,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
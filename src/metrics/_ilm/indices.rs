@@ -0,0 +1,61 @@
+use elasticsearch::ilm::IlmExplainLifecycleParts;
+
+use super::responses::IlmExplainResponse;
+
+/// Per index lifecycle state, metrics cardinality grows with number
+/// of managed indices
+pub(crate) const SUBSYSTEM: &str = "ilm_indices";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/ilm-explain-lifecycle.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .ilm()
+                .explain_lifecycle(IlmExplainLifecycleParts::Index("*"))
+                .only_managed(true)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response
+        .json::<IlmExplainResponse>()
+        .await?
+        .into_index_values(chrono::Utc::now().timestamp_millis());
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_ilm_indices() {
+    use serde_json::json;
+
+    let explain: IlmExplainResponse =
+        serde_json::from_str(include_str!("../../tests/files/ilm_explain.json"))
+            .expect("valid json");
+
+    // 2021-08-21T12:00:00Z
+    let values = explain.into_index_values(1_629_547_200_000);
+
+    // Unmanaged index is skipped
+    assert_eq!(values.len(), 3);
+    assert!(values.contains(&json!({
+        "index": "logs-000041",
+        "policy": "logs",
+        "phase": "hot",
+        "action": "ERROR",
+        "step": "ERROR",
+        "failed_step": "attempt-rollover",
+        "error": true,
+        "age_millis": 172_800_000,
+        "time_in_phase_millis": 172_800_000,
+        "time_in_step_millis": 3_600_000,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
@@ -0,0 +1,4 @@
+mod responses;
+
+pub(crate) mod explain;
+pub(crate) mod indices;
@@ -0,0 +1,151 @@
+use serde_json::{json, Value};
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+
+/// Step of indices which failed to execute lifecycle step
+const ERROR_STEP: &str = "ERROR";
+
+/// ILM explain response
+#[derive(Debug, Deserialize)]
+pub(crate) struct IlmExplainResponse {
+    #[serde(default)]
+    indices: HashMap<String, IlmIndex>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IlmIndex {
+    #[serde(default)]
+    managed: bool,
+    #[serde(default)]
+    policy: Option<String>,
+    #[serde(default)]
+    lifecycle_date_millis: Option<i64>,
+    #[serde(default)]
+    phase: Option<String>,
+    #[serde(default)]
+    phase_time_millis: Option<i64>,
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    step: Option<String>,
+    #[serde(default)]
+    step_time_millis: Option<i64>,
+    #[serde(default)]
+    failed_step: Option<String>,
+}
+
+/// Indices of the same policy and phase
+#[derive(Debug, Default)]
+struct PhaseGroup {
+    oldest_index_age_millis: i64,
+    time_in_phase_max_millis: i64,
+}
+
+/// Label value, missing values are exported as empty labels so that label
+/// set of metric is the same for all indices
+fn label(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn since(now_millis: i64, time_millis: Option<i64>) -> i64 {
+    time_millis
+        .map(|time_millis| cmp::max(now_millis - time_millis, 0))
+        .unwrap_or(0)
+}
+
+impl IlmExplainResponse {
+    /// Aggregate managed indices by policy, phase and step, metrics
+    /// cardinality does not grow with number of indices
+    pub(crate) fn into_values(self, now_millis: i64) -> Vec<Value> {
+        let mut unmanaged = 0;
+        let mut steps: BTreeMap<(String, String, String), u64> = BTreeMap::new();
+        let mut phases: BTreeMap<(String, String), PhaseGroup> = BTreeMap::new();
+        let mut errors: BTreeMap<(String, String), u64> = BTreeMap::new();
+
+        for index in self.indices.values() {
+            if !index.managed {
+                unmanaged += 1;
+                continue;
+            }
+
+            let policy = label(&index.policy);
+            let phase = label(&index.phase);
+            let step = label(&index.step);
+
+            if step == ERROR_STEP {
+                *errors
+                    .entry((policy.clone(), label(&index.failed_step)))
+                    .or_insert(0) += 1;
+            }
+
+            *steps
+                .entry((policy.clone(), phase.clone(), step))
+                .or_insert(0) += 1;
+
+            let group = phases.entry((policy, phase)).or_default();
+            group.oldest_index_age_millis = cmp::max(
+                group.oldest_index_age_millis,
+                since(now_millis, index.lifecycle_date_millis),
+            );
+            group.time_in_phase_max_millis = cmp::max(
+                group.time_in_phase_max_millis,
+                since(now_millis, index.phase_time_millis),
+            );
+        }
+
+        let mut values = vec![json!({
+            "managed_indices_count": self.indices.len() - unmanaged,
+            "unmanaged_indices_count": unmanaged,
+        })];
+
+        values.extend(steps.into_iter().map(|((policy, phase, step), count)| {
+            json!({
+                "policy": policy,
+                "phase": phase,
+                "step": step,
+                "indices_count": count,
+            })
+        }));
+
+        values.extend(phases.into_iter().map(|((policy, phase), group)| {
+            json!({
+                "policy": policy,
+                "phase": phase,
+                "oldest_index_age_millis": group.oldest_index_age_millis,
+                "time_in_phase_max_millis": group.time_in_phase_max_millis,
+            })
+        }));
+
+        values.extend(errors.into_iter().map(|((policy, failed_step), count)| {
+            json!({
+                "policy": policy,
+                "failed_step": failed_step,
+                "error_indices_count": count,
+            })
+        }));
+
+        values
+    }
+
+    /// Lifecycle state of each managed index
+    pub(crate) fn into_index_values(self, now_millis: i64) -> Vec<Value> {
+        self.indices
+            .into_iter()
+            .filter(|(_, index)| index.managed)
+            .map(|(name, index)| {
+                json!({
+                    "index": name,
+                    "policy": label(&index.policy),
+                    "phase": label(&index.phase),
+                    "action": label(&index.action),
+                    "step": label(&index.step),
+                    "failed_step": label(&index.failed_step),
+                    "error": index.step.as_deref() == Some(ERROR_STEP),
+                    "age_millis": since(now_millis, index.lifecycle_date_millis),
+                    "time_in_phase_millis": since(now_millis, index.phase_time_millis),
+                    "time_in_step_millis": since(now_millis, index.step_time_millis),
+                })
+            })
+            .collect()
+    }
+}
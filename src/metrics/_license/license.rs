@@ -0,0 +1,72 @@
+use super::responses::LicenseResponse;
+
+pub(crate) const SUBSYSTEM: &str = "license";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/get-license.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .license()
+                .get()
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                // Return local information, do not retrieve the state from master node (default: false)
+                .local(true)
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response
+        .json::<LicenseResponse>()
+        .await?
+        .into_values(chrono::Utc::now().timestamp_millis());
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_license() {
+    use serde_json::json;
+
+    let license: LicenseResponse =
+        serde_json::from_str(include_str!("../../tests/files/license.json")).expect("valid json");
+
+    // 2021-08-21T12:00:00Z
+    let values = license.into_values(1_629_547_200_000);
+
+    assert_eq!(
+        values,
+        vec![
+            json!({ "type": "platinum", "status": "active", "info": 1 }),
+            json!({
+                "expiry_timestamp_seconds": 1_630_152_000,
+                "expires_in_seconds": 604_800,
+                "max_nodes": 10,
+            }),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+
+    let basic: LicenseResponse = serde_json::from_value(json!({
+        "license": {
+            "status": "active",
+            "uid": "cbff45e7-c553-41f7-ae4f-9205eabd80xx",
+            "type": "basic",
+            "issue_date": "2021-08-01T00:00:00.000Z",
+            "issue_date_in_millis": 1627776000000u64,
+            "max_nodes": 1000,
+            "issued_to": "elasticsearch",
+            "issuer": "elasticsearch",
+            "start_date_in_millis": -1
+        }
+    }))
+    .expect("valid json");
+
+    // Basic license does not expire
+    assert_eq!(basic.into_values(1_629_547_200_000).len(), 1);
+}
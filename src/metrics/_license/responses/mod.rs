@@ -0,0 +1,42 @@
+use serde_json::{json, Value};
+
+/// License response
+#[derive(Debug, Deserialize)]
+pub(crate) struct LicenseResponse {
+    license: License,
+}
+
+#[derive(Debug, Deserialize)]
+struct License {
+    status: String,
+    #[serde(rename = "type")]
+    license_type: String,
+    /// Missing for basic license, which does not expire
+    #[serde(default)]
+    expiry_date_in_millis: Option<i64>,
+    #[serde(default)]
+    max_nodes: Option<i64>,
+}
+
+impl LicenseResponse {
+    /// License info labeled by type and status, expiry as timestamp and seconds until expiry
+    pub(crate) fn into_values(self, now_millis: i64) -> Vec<Value> {
+        let license = self.license;
+
+        let mut values = vec![json!({
+            "type": license.license_type,
+            "status": license.status,
+            "info": 1,
+        })];
+
+        if let Some(expiry) = license.expiry_date_in_millis {
+            values.push(json!({
+                "expiry_timestamp_seconds": expiry / 1000,
+                "expires_in_seconds": (expiry - now_millis) / 1000,
+                "max_nodes": license.max_nodes,
+            }));
+        }
+
+        values
+    }
+}
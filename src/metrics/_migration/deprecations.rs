@@ -0,0 +1,50 @@
+use elasticsearch::migration::MigrationDeprecationsParts;
+
+use super::responses::DeprecationsResponse;
+
+pub(crate) const SUBSYSTEM: &str = "deprecations";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/migration-api-deprecation.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .migration()
+                .deprecations(MigrationDeprecationsParts::None)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response.json::<DeprecationsResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_deprecations() {
+    use serde_json::json;
+
+    let deprecations: DeprecationsResponse =
+        serde_json::from_str(include_str!("../../tests/files/deprecations.json"))
+            .expect("valid json");
+
+    let values = deprecations.into_values();
+
+    assert_eq!(
+        values,
+        vec![
+            json!({ "category": "cluster", "level": "critical", "issues": 1 }),
+            json!({ "category": "index", "level": "critical", "issues": 1 }),
+            json!({ "category": "index", "level": "warning", "issues": 2 }),
+            json!({ "category": "node", "level": "warning", "issues": 1 }),
+            json!({ "category": "index", "affected_count": 2 }),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
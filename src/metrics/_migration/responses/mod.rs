@@ -0,0 +1,65 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Deprecations response, issues are grouped by category, e.g.:
+/// "cluster_settings": [...] or keyed by affected resource, e.g.:
+/// "index_settings": {"logs": [...]}
+#[derive(Debug, Deserialize)]
+pub(crate) struct DeprecationsResponse(Map<String, Value>);
+
+#[derive(Debug, Deserialize)]
+struct DeprecationIssue {
+    level: String,
+}
+
+fn issues(value: &Value) -> Vec<DeprecationIssue> {
+    serde_json::from_value(value.clone()).unwrap_or_default()
+}
+
+impl DeprecationsResponse {
+    /// Issues counted by category and level, keyed categories additionally
+    /// count affected resources, e.g.: indices
+    pub(crate) fn into_values(self) -> Vec<Value> {
+        let mut levels: BTreeMap<(String, String), u64> = BTreeMap::new();
+        let mut affected: BTreeMap<String, u64> = BTreeMap::new();
+
+        for (key, value) in self.0.iter() {
+            let category = key.trim_end_matches("_settings").to_string();
+
+            let groups = match value {
+                Value::Array(_) => vec![issues(value)],
+                Value::Object(resources) => {
+                    let groups: Vec<Vec<DeprecationIssue>> = resources
+                        .values()
+                        .map(issues)
+                        .filter(|issues| !issues.is_empty())
+                        .collect();
+
+                    let _ = affected.insert(category.clone(), groups.len() as u64);
+
+                    groups
+                }
+                _ => continue,
+            };
+
+            for issue in groups.into_iter().flatten() {
+                *levels.entry((category.clone(), issue.level)).or_insert(0) += 1;
+            }
+        }
+
+        let mut values: Vec<Value> = levels
+            .into_iter()
+            .map(|((category, level), count)| {
+                json!({ "category": category, "level": level, "issues": count })
+            })
+            .collect();
+
+        values.extend(
+            affected
+                .into_iter()
+                .map(|(category, count)| json!({ "category": category, "affected_count": count })),
+        );
+
+        values
+    }
+}
@@ -7,13 +7,16 @@ pub(crate) const SUBSYSTEM: &str = "nodes_info";
 // https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-nodes-stats.html
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .nodes()
-        .info(NodesInfoParts::Metric(
-            &exporter.options().path_parameters_for_subsystem(SUBSYSTEM),
-        ))
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .nodes()
+                .info(NodesInfoParts::Metric(
+                    &exporter.options().path_parameters_for_subsystem(SUBSYSTEM),
+                ))
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     let values = response
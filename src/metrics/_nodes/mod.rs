@@ -3,3 +3,4 @@ mod responses;
 pub(crate) mod info;
 pub(crate) mod stats;
 pub(crate) mod usage;
+pub(crate) mod versions;
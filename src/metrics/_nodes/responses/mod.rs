@@ -1,5 +1,5 @@
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 
 use crate::{
     metadata::{IdToMetadata, NodeData},
@@ -34,6 +34,115 @@ impl NodesResponse {
 
         values
     }
+
+    /// Ingest pipeline stats keyed by pipeline instead of flattening
+    /// `ingest.pipelines` map, per processor stats are included on request
+    pub(crate) async fn ingest_pipelines_values(
+        &self,
+        metadata: &IdToMetadata,
+        processors: bool,
+    ) -> Vec<Value> {
+        let mut values: Vec<Value> = Vec::new();
+
+        let metadata_read = metadata.read().await;
+
+        for (node_id, data) in self.nodes.iter() {
+            let node_metadata = match metadata_read.get(node_id) {
+                Some(node_metadata) => node_metadata,
+                None => continue,
+            };
+
+            let pipelines = match data["ingest"]["pipelines"].as_object() {
+                Some(pipelines) => pipelines,
+                None => continue,
+            };
+
+            for (pipeline, stats) in pipelines {
+                let mut value = json!({
+                    "pipeline": pipeline,
+                    "ingest_pipelines_count": stats["count"],
+                    "ingest_pipelines_time_in_millis": stats["time_in_millis"],
+                    "ingest_pipelines_current": stats["current"],
+                    "ingest_pipelines_failed": stats["failed"],
+                });
+                inject_label(&mut value, node_metadata, &[]);
+                values.push(value);
+
+                if !processors {
+                    continue;
+                }
+
+                for ((processor_type, processor_tag), stats) in processors_stats(stats) {
+                    let mut value = json!({
+                        "pipeline": pipeline,
+                        "processor_type": processor_type,
+                        "processor_tag": processor_tag,
+                        "ingest_pipelines_processors_count": stats.count,
+                        "ingest_pipelines_processors_time_in_millis": stats.time_in_millis,
+                        "ingest_pipelines_processors_current": stats.current,
+                        "ingest_pipelines_processors_failed": stats.failed,
+                    });
+                    inject_label(&mut value, node_metadata, &[]);
+                    values.push(value);
+                }
+            }
+        }
+
+        values
+    }
+}
+
+/// Ingest processor stats
+#[derive(Debug, Default, Deserialize)]
+struct ProcessorStats {
+    #[serde(default)]
+    count: u64,
+    #[serde(default)]
+    time_in_millis: u64,
+    #[serde(default)]
+    current: u64,
+    #[serde(default)]
+    failed: u64,
+}
+
+/// Pipeline processors are listed as array of single key objects, where key is
+/// processor type optionally followed by `:tag`, e.g.:
+/// "processors": [{"set:add-host": {"type": "set", "stats": {...}}}]
+///
+/// Untagged processors of the same type are summed up
+fn processors_stats(pipeline: &Value) -> BTreeMap<(String, String), ProcessorStats> {
+    let mut processors: BTreeMap<(String, String), ProcessorStats> = BTreeMap::new();
+
+    let entries = pipeline["processors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+        .flatten();
+
+    for (key, processor) in entries {
+        let processor_type = processor["type"]
+            .as_str()
+            .unwrap_or_else(|| key.split(':').next().unwrap_or(key));
+
+        let processor_tag = key
+            .strip_prefix(processor_type)
+            .and_then(|tag| tag.strip_prefix(':'))
+            .unwrap_or("");
+
+        let stats: ProcessorStats =
+            serde_json::from_value(processor["stats"].clone()).unwrap_or_default();
+
+        let entry = processors
+            .entry((processor_type.to_string(), processor_tag.to_string()))
+            .or_default();
+        entry.count += stats.count;
+        entry.time_in_millis += stats.time_in_millis;
+        entry.current += stats.current;
+        entry.failed += stats.failed;
+    }
+
+    processors
 }
 
 fn inject_label(value: &mut Value, node_data: &NodeData, keys_to_remove: &[&'static str]) {
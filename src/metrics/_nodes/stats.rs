@@ -7,26 +7,39 @@ pub(crate) const SUBSYSTEM: &str = "nodes_stats";
 // https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-nodes-stats.html
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .nodes()
-        .stats(NodesStatsParts::Metric(
-            &exporter.options().path_parameters_for_subsystem(SUBSYSTEM),
-        ))
-        .fields(&exporter.options().query_fields_for_subsystem(SUBSYSTEM))
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .nodes()
+                .stats(NodesStatsParts::Metric(
+                    &exporter.options().path_parameters_for_subsystem(SUBSYSTEM),
+                ))
+                .fields(&exporter.options().query_fields_for_subsystem(SUBSYSTEM))
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
-    let values = response
-        .json::<NodesResponse>()
-        .await?
+    let response = response.json::<NodesResponse>().await?;
+
+    let pipelines = response
+        .ingest_pipelines_values(
+            exporter.nodes_metadata(),
+            exporter.options().exporter_nodes_stats_ingest_processors,
+        )
+        .await;
+
+    let mut values = response
         .into_values(exporter.nodes_metadata(), REMOVE_KEYS)
         .await;
+    values.extend(pipelines);
 
     Ok(metric::from_values(values))
 }
 
 // NOTE:
+// "pipelines" are exported separately keyed by pipeline
+//
 // enabling adaptive_selection exposes metrics in nanoseconds, e.g.: "avg_response_time_ns": 196669342
 const REMOVE_KEYS: &[&str] = &[
     "timestamp",
@@ -83,3 +96,72 @@ async fn test_nodes_stats() {
 
     assert!(values.get("timestamp").is_some());
 }
+
+#[tokio::test]
+async fn test_nodes_stats_ingest_pipelines() {
+    use serde_json::json;
+    use tokio::sync::RwLock;
+
+    use crate::metadata::node_data::{NodeData, NodeDataMap};
+
+    let stats: NodesResponse =
+        serde_json::from_str(include_str!("../../tests/files/nodes_stats_ingest.json"))
+            .expect("valid json");
+
+    let mut metadata = NodeDataMap::new();
+    let _ = metadata.insert(
+        "U-WnGaTpRxucgde3miiDWw".into(),
+        NodeData {
+            name: "m1-nodename.example.com".into(),
+            ip: "10.0.0.1".into(),
+            version: "7.10.2".into(),
+        },
+    );
+    let metadata = RwLock::new(metadata);
+
+    let values = stats.ingest_pipelines_values(&metadata, false).await;
+    assert_eq!(values.len(), 2);
+    assert!(values.contains(&json!({
+        "name": "m1-nodename.example.com",
+        "ip": "10.0.0.1",
+        "vin_cluster_version": "7.10.2",
+        "pipeline": "logs",
+        "ingest_pipelines_count": 1500,
+        "ingest_pipelines_time_in_millis": 800,
+        "ingest_pipelines_current": 2,
+        "ingest_pipelines_failed": 3,
+    })));
+
+    let values = stats.ingest_pipelines_values(&metadata, true).await;
+    // 2 pipelines and 3 distinct processors of "logs" pipeline
+    assert_eq!(values.len(), 5);
+    assert!(values.contains(&json!({
+        "name": "m1-nodename.example.com",
+        "ip": "10.0.0.1",
+        "vin_cluster_version": "7.10.2",
+        "pipeline": "logs",
+        "processor_type": "set",
+        "processor_tag": "add-host",
+        "ingest_pipelines_processors_count": 1500,
+        "ingest_pipelines_processors_time_in_millis": 20,
+        "ingest_pipelines_processors_current": 0,
+        "ingest_pipelines_processors_failed": 0,
+    })));
+    // Untagged processors of the same type are summed up
+    assert!(values.contains(&json!({
+        "name": "m1-nodename.example.com",
+        "ip": "10.0.0.1",
+        "vin_cluster_version": "7.10.2",
+        "pipeline": "logs",
+        "processor_type": "remove",
+        "processor_tag": "",
+        "ingest_pipelines_processors_count": 2994,
+        "ingest_pipelines_processors_time_in_millis": 22,
+        "ingest_pipelines_processors_current": 0,
+        "ingest_pipelines_processors_failed": 0,
+    })));
+
+    // Pipelines are not flattened into node values
+    let values = stats.into_values(&metadata, REMOVE_KEYS).await;
+    assert!(values[0]["ingest"].get("pipelines").is_none());
+}
@@ -6,11 +6,14 @@ pub(crate) const SUBSYSTEM: &str = "nodes_usage";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .nodes()
-        .usage(NodesUsageParts::None)
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .nodes()
+                .usage(NodesUsageParts::None)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     let values = response
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+/// Cluster-wide summary of node versions from nodes metadata, metadata is
+/// refreshed every `exporter_metadata_refresh_interval`
+pub(crate) const SUBSYSTEM: &str = "nodes_versions";
+
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let versions = versions(&*exporter.nodes_metadata().read().await);
+
+    Ok(metric::from_values(into_values(versions)))
+}
+
+/// Number of nodes per Elasticsearch version
+fn versions(metadata: &crate::metadata::node_data::NodeDataMap) -> BTreeMap<String, u64> {
+    let mut versions: BTreeMap<String, u64> = BTreeMap::new();
+
+    for node in metadata.values() {
+        *versions.entry(node.version.clone()).or_insert(0) += 1;
+    }
+
+    versions
+}
+
+/// Number of distinct versions, more than one means version skew
+fn into_values(versions: BTreeMap<String, u64>) -> Vec<Value> {
+    let mut values = vec![serde_json::json!({ "count": versions.len() })];
+
+    values.extend(
+        versions
+            .into_iter()
+            .map(|(version, nodes)| serde_json::json!({ "version": version, "nodes": nodes })),
+    );
+
+    values
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_nodes_versions() {
+    use crate::metadata::node_data::{update_map, NodeData, NodeDataMap};
+    use serde_json::json;
+
+    let mut metadata = NodeDataMap::new();
+    for (id, version) in &[("a", "7.10.2"), ("b", "7.10.2"), ("c", "7.13.4")] {
+        let _ = metadata.insert(
+            id.to_string(),
+            NodeData {
+                version: version.to_string(),
+                ..Default::default()
+            },
+        );
+    }
+
+    let values = into_values(versions(&metadata));
+
+    assert_eq!(
+        values,
+        vec![
+            json!({ "count": 2 }),
+            json!({ "version": "7.10.2", "nodes": 2 }),
+            json!({ "version": "7.13.4", "nodes": 1 }),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+
+    // Node of the old version left after rolling upgrade
+    let mut refreshed = metadata.clone();
+    let _ = refreshed.remove("c");
+    update_map(&mut metadata, refreshed);
+
+    assert_eq!(
+        into_values(versions(&metadata)),
+        vec![
+            json!({ "count": 1 }),
+            json!({ "version": "7.10.2", "nodes": 2 }),
+        ]
+    );
+}
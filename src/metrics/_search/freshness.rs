@@ -0,0 +1,105 @@
+use elasticsearch::SearchParts;
+
+use super::responses::FreshnessResponse;
+
+/// Patterns are configured with `elasticsearch_path_parameters`,
+/// e.g.: index_freshness=logs-*,metrics-*
+pub(crate) const SUBSYSTEM: &str = "index_freshness";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/search-aggregations-metrics-max-aggregation.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    // Patterns are searched concurrently, so poll takes single subsystem timeout at most
+    let searches = exporter
+        .options()
+        .path_parameters_for_subsystem(SUBSYSTEM)
+        .into_iter()
+        .map(|pattern| {
+            let search = tokio::spawn(freshness(exporter.clone(), pattern.to_string()));
+            (pattern.to_string(), search)
+        })
+        .collect::<Vec<_>>();
+
+    let mut values: Vec<Value> = Vec::new();
+
+    // Single failing pattern, e.g.: missing timestamp field mapping or
+    // timed out search, must not hide freshness of other patterns
+    for (pattern, search) in searches {
+        match search.await {
+            Ok(Ok(value)) => values.push(value),
+            Ok(Err(e)) => error!("{} pattern {} err {}", SUBSYSTEM, pattern, e),
+            Err(e) => error!("{} pattern {} err {}", SUBSYSTEM, pattern, e),
+        }
+    }
+
+    Ok(metric::from_values(values))
+}
+
+async fn freshness(exporter: Exporter, pattern: String) -> Result<Value, elasticsearch::Error> {
+    let options = exporter.options();
+    let timeout = options.timeout_for_subsystem(SUBSYSTEM);
+    let body = &FreshnessResponse::request_body(
+        &options.exporter_index_freshness_timestamp_field,
+        options.exporter_index_freshness_window.as_secs(),
+    );
+    let pattern = pattern.as_str();
+
+    let response = exporter
+        .send(|client| async move {
+            client
+                .search(SearchParts::Index(&[pattern]))
+                .allow_no_indices(true)
+                .ignore_unavailable(true)
+                .request_timeout(timeout)
+                .body(body.clone())
+                .send()
+                .await
+        })
+        .await?
+        .error_for_status_code()?;
+
+    Ok(response
+        .json::<FreshnessResponse>()
+        .await?
+        .into_value(pattern, chrono::Utc::now().timestamp_millis()))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_index_freshness() {
+    use serde_json::json;
+
+    let body = FreshnessResponse::request_body("@timestamp", 300);
+    assert_eq!(
+        body["aggs"]["window"]["filter"]["range"]["@timestamp"]["gte"],
+        "now-300s"
+    );
+
+    let response: FreshnessResponse =
+        serde_json::from_str(include_str!("../../tests/files/index_freshness.json"))
+            .expect("valid json");
+
+    // 2021-08-21T12:00:00Z
+    let value = response.into_value("logs-*", 1_629_547_200_000);
+    assert_eq!(
+        value,
+        json!({
+            "pattern": "logs-*",
+            "window_docs_count": 1832,
+            "newest_document_age_millis": 4_500,
+        })
+    );
+
+    let empty: FreshnessResponse = serde_json::from_value(json!({
+        "aggregations": {
+            "newest": { "value": null },
+            "window": { "doc_count": 0 }
+        }
+    }))
+    .expect("valid json");
+    let value = empty.into_value("metrics-*", 1_629_547_200_000);
+    assert!(value.get("newest_document_age_millis").is_none());
+
+    let metrics = metric::from_values(vec![value]);
+    assert!(!metrics.is_empty());
+}
@@ -0,0 +1,4 @@
+mod responses;
+
+pub(crate) mod freshness;
+pub(crate) mod queries;
@@ -0,0 +1,187 @@
+use elasticsearch::SearchParts;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use super::responses::QueryResponse;
+use crate::QueryOptions;
+
+/// Searches are configured with `exporter_queries` in configuration file
+pub(crate) const SUBSYSTEM: &str = "queries";
+
+/// Last values of queries with interval longer than subsystem poll interval,
+/// re-exported to keep metrics alive, keyed by query name
+pub(crate) type QueryCache = Mutex<HashMap<String, CachedQuery>>;
+
+/// Query values exported until query interval elapses
+#[derive(Debug)]
+pub(crate) struct CachedQuery {
+    query: QueryOptions,
+    searched: Instant,
+    values: Vec<Value>,
+}
+
+fn request_body(query: &QueryOptions) -> Value {
+    let mut body = json!({
+        "size": 0,
+        "track_total_hits": true,
+        "query": query.query.clone().unwrap_or_else(|| json!({ "match_all": {} })),
+    });
+
+    if let Some(ref aggregations) = query.aggregations {
+        body["aggs"] = aggregations.clone();
+    }
+
+    body
+}
+
+fn cached(exporter: &Exporter, query: &QueryOptions) -> Option<Vec<Value>> {
+    let interval = query.interval?;
+    let cache = exporter.queries_cache().lock().expect("queries cache lock");
+
+    cache
+        .get(&query.name)
+        .filter(|cached| &cached.query == query && cached.searched.elapsed() < interval)
+        .map(|cached| cached.values.clone())
+}
+
+async fn search(
+    exporter: &Exporter,
+    query: &QueryOptions,
+) -> Result<Vec<Value>, elasticsearch::Error> {
+    let options = exporter.options();
+    let timeout = options.timeout_for_subsystem(SUBSYSTEM);
+
+    let response = exporter
+        .send(|client| async move {
+            client
+                .search(SearchParts::Index(&[&query.index]))
+                .allow_no_indices(true)
+                .ignore_unavailable(true)
+                .request_timeout(timeout)
+                .body(request_body(query))
+                .send()
+                .await
+        })
+        .await?
+        .error_for_status_code()?;
+
+    Ok(response
+        .json::<QueryResponse>()
+        .await?
+        .into_values(&query.name, options.exporter_queries_max_buckets))
+}
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/search-search.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let options = exporter.options();
+
+    let mut values: Vec<Value> = Vec::new();
+
+    for query in options.exporter_queries.iter() {
+        if let Some(cached) = cached(exporter, query) {
+            values.extend(cached);
+            continue;
+        }
+
+        // Single failing query must not hide results of other queries
+        let query_values = match search(exporter, query).await {
+            Ok(query_values) => query_values,
+            Err(e) => {
+                error!("{} query {} err {}", SUBSYSTEM, query.name, e);
+                continue;
+            }
+        };
+
+        let _ = exporter
+            .queries_cache()
+            .lock()
+            .expect("queries cache lock")
+            .insert(
+                query.name.clone(),
+                CachedQuery {
+                    query: query.clone(),
+                    searched: Instant::now(),
+                    values: query_values.clone(),
+                },
+            );
+
+        values.extend(query_values);
+    }
+
+    // Forget queries removed on configuration reload
+    exporter
+        .queries_cache()
+        .lock()
+        .expect("queries cache lock")
+        .retain(|name, _| {
+            options
+                .exporter_queries
+                .iter()
+                .any(|query| &query.name == name)
+        });
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_queries() {
+    let query: QueryOptions = serde_json::from_value(json!({
+        "name": "errors",
+        "index": "logs-*",
+        "query": { "term": { "level": "error" } },
+        "aggregations": {
+            "by_service": { "terms": { "field": "service", "size": 10 } }
+        },
+        "interval": "1m"
+    }))
+    .expect("valid query");
+
+    let body = request_body(&query);
+    assert_eq!(body["query"]["term"]["level"], "error");
+    assert_eq!(body["aggs"]["by_service"]["terms"]["field"], "service");
+
+    let response: QueryResponse =
+        serde_json::from_str(include_str!("../../tests/files/queries.json")).expect("valid json");
+
+    let values = response.into_values("errors", 3);
+
+    assert_eq!(
+        values[0],
+        json!({ "query": "errors", "hits_count": 5120, "dropped_buckets": 1 })
+    );
+    assert!(values.contains(&json!({
+        "query": "errors",
+        "aggregation": "by_service",
+        "key": "checkout",
+        "bucket_doc_count": 4096,
+    })));
+    assert!(values.contains(&json!({
+        "query": "errors",
+        "aggregation": "by_service",
+        "key": "checkout",
+        "metric": "latency",
+        "bucket_value": 12.5,
+    })));
+    assert!(values.contains(&json!({
+        "query": "errors",
+        "aggregation": "per_minute",
+        "key": "2021-08-21T11:59:00.000Z",
+        "bucket_doc_count": 256,
+    })));
+    assert!(values.contains(&json!({
+        "query": "errors",
+        "aggregation": "max_latency",
+        "aggregation_value": 480.0,
+    })));
+    // Fourth bucket is over the cap
+    assert!(!values
+        .iter()
+        .any(|value| value["key"] == "2021-08-21T12:00:00.000Z"));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
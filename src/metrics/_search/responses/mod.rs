@@ -0,0 +1,158 @@
+use serde_json::{json, Value};
+use std::cmp;
+
+/// Index freshness search response, `size: 0` with `newest` max and
+/// `window` filter aggregations
+#[derive(Debug, Deserialize)]
+pub(crate) struct FreshnessResponse {
+    aggregations: FreshnessAggregations,
+}
+
+#[derive(Debug, Deserialize)]
+struct FreshnessAggregations {
+    newest: MaxAggregation,
+    window: FilterAggregation,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaxAggregation {
+    /// Null when pattern matches no documents with timestamp field
+    value: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilterAggregation {
+    doc_count: u64,
+}
+
+impl FreshnessResponse {
+    /// Search request body of freshness probe
+    pub(crate) fn request_body(timestamp_field: &str, window_secs: u64) -> Value {
+        json!({
+            "size": 0,
+            "track_total_hits": false,
+            "aggs": {
+                "newest": {
+                    "max": { "field": timestamp_field }
+                },
+                "window": {
+                    "filter": {
+                        "range": {
+                            timestamp_field: { "gte": format!("now-{}s", window_secs) }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Age of the newest document and count of documents in window labeled by
+    /// pattern instead of concrete index
+    pub(crate) fn into_value(self, pattern: &str, now_millis: i64) -> Value {
+        let mut value = json!({
+            "pattern": pattern,
+            "window_docs_count": self.aggregations.window.doc_count,
+        });
+
+        if let Some(newest) = self.aggregations.newest.value {
+            value["newest_document_age_millis"] = json!(cmp::max(now_millis - newest as i64, 0));
+        }
+
+        value
+    }
+}
+
+/// User-defined query search response
+#[derive(Debug, Deserialize)]
+pub(crate) struct QueryResponse(Value);
+
+/// Buckets of terms and histogram aggregations are arrays, while buckets
+/// of filters aggregation are keyed by filter name
+fn bucket_entries(result: &Value) -> Vec<(String, &Value)> {
+    match &result["buckets"] {
+        Value::Array(buckets) => buckets
+            .iter()
+            .map(|bucket| (bucket_key(bucket), bucket))
+            .collect(),
+        Value::Object(buckets) => buckets
+            .iter()
+            .map(|(key, bucket)| (key.clone(), bucket))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Date histogram buckets carry formatted `key_as_string`
+fn bucket_key(bucket: &Value) -> String {
+    match bucket.get("key_as_string").or_else(|| bucket.get("key")) {
+        Some(Value::String(key)) => key.clone(),
+        Some(key) => key.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Numeric value of single value metric aggregation, e.g.: avg or max
+fn metric_value(result: &Value) -> Option<&Value> {
+    result.get("value").filter(|value| value.is_number())
+}
+
+impl QueryResponse {
+    /// Total hits and aggregation buckets labeled by query, aggregation and
+    /// bucket key, buckets over `max_buckets` are dropped and counted
+    pub(crate) fn into_values(self, query: &str, max_buckets: usize) -> Vec<Value> {
+        // hits.total is plain number before Elasticsearch 7
+        let hits = match &self.0["hits"]["total"] {
+            Value::Object(total) => total.get("value").cloned().unwrap_or(Value::Null),
+            total => total.clone(),
+        };
+
+        let mut values = vec![json!({ "query": query, "hits_count": hits })];
+        let mut buckets = 0;
+        let mut dropped = 0;
+
+        if let Some(aggregations) = self.0["aggregations"].as_object() {
+            for (aggregation, result) in aggregations {
+                if let Some(value) = metric_value(result) {
+                    values.push(json!({
+                        "query": query,
+                        "aggregation": aggregation,
+                        "aggregation_value": value,
+                    }));
+                    continue;
+                }
+
+                for (key, bucket) in bucket_entries(result) {
+                    if buckets >= max_buckets {
+                        dropped += 1;
+                        continue;
+                    }
+                    buckets += 1;
+
+                    values.push(json!({
+                        "query": query,
+                        "aggregation": aggregation,
+                        "key": key,
+                        "bucket_doc_count": bucket["doc_count"],
+                    }));
+
+                    let sub_aggregations = bucket.as_object().into_iter().flatten();
+                    for (metric, sub_result) in sub_aggregations {
+                        if let Some(value) = metric_value(sub_result) {
+                            values.push(json!({
+                                "query": query,
+                                "aggregation": aggregation,
+                                "key": key,
+                                "metric": metric,
+                                "bucket_value": value,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+
+        values[0]["dropped_buckets"] = json!(dropped);
+
+        values
+    }
+}
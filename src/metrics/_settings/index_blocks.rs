@@ -0,0 +1,55 @@
+use elasticsearch::indices::IndicesGetSettingsParts;
+
+use super::responses::IndexBlocksResponse;
+
+pub(crate) const SUBSYSTEM: &str = "index_blocks";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/index-modules-blocks.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .indices()
+                .get_settings(IndicesGetSettingsParts::Name(&["index.blocks.*"]))
+                .flat_settings(true)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response.json::<IndexBlocksResponse>().await?.into_values();
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_index_blocks() {
+    use serde_json::json;
+
+    let blocks: IndexBlocksResponse =
+        serde_json::from_str(include_str!("../../tests/files/index_blocks.json"))
+            .expect("valid json");
+
+    let values = blocks.into_values();
+
+    assert_eq!(
+        values,
+        vec![
+            json!({ "block": "metadata", "indices_count": 0 }),
+            json!({ "block": "read", "indices_count": 0 }),
+            json!({ "block": "read_only", "indices_count": 1 }),
+            json!({ "block": "read_only_allow_delete", "indices_count": 2 }),
+            json!({ "block": "write", "indices_count": 1 }),
+            json!({ "index": "logs-2021.08.01", "block": "read_only_allow_delete", "blocked": true }),
+            json!({ "index": "logs-2021.08.02", "block": "read_only_allow_delete", "blocked": true }),
+            json!({ "index": "shrink-source", "block": "write", "blocked": true }),
+            json!({ "index": "snapshot-restored", "block": "read_only", "blocked": true }),
+        ]
+    );
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
@@ -0,0 +1,58 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Index block types, counted even when no index is blocked
+const BLOCKS: &[&str] = &[
+    "metadata",
+    "read",
+    "read_only",
+    "read_only_allow_delete",
+    "write",
+];
+
+/// Flat index.blocks.* settings of each index
+#[derive(Debug, Deserialize)]
+pub(crate) struct IndexBlocksResponse(BTreeMap<String, IndexSettings>);
+
+#[derive(Debug, Deserialize)]
+struct IndexSettings {
+    #[serde(default)]
+    settings: Map<String, Value>,
+}
+
+impl IndexBlocksResponse {
+    /// Number of indices per block type and blocked indices labeled by block
+    pub(crate) fn into_values(self) -> Vec<Value> {
+        let mut blocks: BTreeMap<String, u64> =
+            BLOCKS.iter().map(|block| (block.to_string(), 0)).collect();
+        let mut values: Vec<Value> = Vec::new();
+
+        for (index, settings) in self.0 {
+            for (setting, value) in settings.settings {
+                let block = match setting.strip_prefix("index.blocks.") {
+                    Some(block) => block.to_string(),
+                    None => continue,
+                };
+
+                let blocked = match value {
+                    Value::Bool(blocked) => blocked,
+                    Value::String(ref blocked) => blocked == "true",
+                    _ => false,
+                };
+
+                if !blocked {
+                    continue;
+                }
+
+                *blocks.entry(block.clone()).or_insert(0) += 1;
+                values.push(json!({ "index": index, "block": block, "blocked": true }));
+            }
+        }
+
+        blocks
+            .into_iter()
+            .map(|(block, count)| json!({ "block": block, "indices_count": count }))
+            .chain(values)
+            .collect()
+    }
+}
@@ -0,0 +1,338 @@
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+// NOTE: `MetricType` discards keys ending with "timestamp" or "epoch", timestamps
+// are exported with "_timestamp_seconds" keys and ages with "_age_millis" keys
+// which are converted to seconds
+
+/// /_slm/policy response keyed by policy ID
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SlmPoliciesResponse(HashMap<String, SlmPolicy>);
+
+#[derive(Debug, Deserialize)]
+struct SlmPolicy {
+    policy: SlmPolicyDefinition,
+    #[serde(default)]
+    last_success: Option<SlmInvocation>,
+    #[serde(default)]
+    last_failure: Option<SlmInvocation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlmPolicyDefinition {
+    repository: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlmInvocation {
+    /// Snapshot start time is reported by Elasticsearch 7.10+
+    #[serde(default)]
+    start_time: Option<i64>,
+    time: i64,
+}
+
+impl SlmPoliciesResponse {
+    /// Whether any SLM policy takes snapshots into given repository
+    pub(crate) fn manages(&self, repository: &str) -> bool {
+        self.0
+            .values()
+            .any(|policy| policy.policy.repository == repository)
+    }
+}
+
+/// /_slm/stats response
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SlmStatsResponse {
+    #[serde(default)]
+    policy_stats: Vec<SlmPolicyStats>,
+    #[serde(flatten)]
+    retention: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlmPolicyStats {
+    policy: String,
+    #[serde(default)]
+    snapshots_taken: u64,
+    #[serde(default)]
+    snapshots_failed: u64,
+    #[serde(default)]
+    snapshots_deleted: u64,
+    #[serde(default)]
+    snapshot_deletion_failures: u64,
+}
+
+/// /_cat/snapshots row
+#[derive(Debug, Deserialize)]
+pub(crate) struct CatSnapshot {
+    repository: String,
+    status: String,
+    #[serde(default)]
+    start_epoch: Option<String>,
+    #[serde(default)]
+    end_epoch: Option<String>,
+    /// Duration in milliseconds, requested with time=ms
+    #[serde(default)]
+    duration: Option<String>,
+}
+
+/// /_snapshot/_status response of currently running snapshots
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SnapshotStatusResponse {
+    #[serde(default)]
+    snapshots: Vec<SnapshotStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotStatus {
+    snapshot: String,
+    repository: String,
+    state: String,
+    #[serde(default)]
+    shards_stats: Map<String, Value>,
+}
+
+/// Responses of snapshot subsystem, SLM responses are missing
+/// if SLM is not available
+#[derive(Debug, Default)]
+pub(crate) struct SnapshotsResponses {
+    pub(crate) policies: Option<SlmPoliciesResponse>,
+    pub(crate) stats: Option<SlmStatsResponse>,
+    /// Snapshots of repositories not managed by SLM
+    pub(crate) snapshots: Vec<CatSnapshot>,
+    pub(crate) status: SnapshotStatusResponse,
+}
+
+fn parse_epoch(epoch: &Option<String>) -> Option<i64> {
+    epoch.as_ref().and_then(|epoch| epoch.parse::<i64>().ok())
+}
+
+/// Insert timestamp and age of event, timestamp is given in milliseconds
+fn insert_event(map: &mut Map<String, Value>, prefix: &str, time_millis: i64, now_millis: i64) {
+    let _ = map.insert(
+        format!("{}_timestamp_seconds", prefix),
+        json!(time_millis / 1000),
+    );
+    let _ = map.insert(
+        format!("{}_age_millis", prefix),
+        json!(std::cmp::max(now_millis - time_millis, 0)),
+    );
+}
+
+/// Successful snapshot end time and duration in milliseconds
+#[derive(Debug)]
+struct Success {
+    time: i64,
+    duration: Option<i64>,
+}
+
+/// Last successful and failed snapshot of repository, failure is given
+/// as end time in milliseconds
+#[derive(Debug, Default)]
+struct RepositorySnapshots<'a> {
+    last_success: Option<Success>,
+    last_failure: Option<i64>,
+    statuses: BTreeMap<&'a str, u64>,
+}
+
+impl<'a> RepositorySnapshots<'a> {
+    fn success(&mut self, success: Success) {
+        match self.last_success {
+            Some(ref last) if last.time > success.time => {}
+            _ => self.last_success = Some(success),
+        }
+    }
+
+    fn failure(&mut self, time: i64) {
+        match self.last_failure {
+            Some(last) if last > time => {}
+            _ => self.last_failure = Some(time),
+        }
+    }
+}
+
+impl SnapshotsResponses {
+    /// Summarize snapshots per SLM policy and per repository, `now_millis`
+    /// is current time used to calculate age of last snapshots
+    pub(crate) fn into_values(self, now_millis: i64) -> Vec<Value> {
+        let mut values = Vec::new();
+
+        let policy_stats = self
+            .stats
+            .as_ref()
+            .map(|stats| {
+                stats
+                    .policy_stats
+                    .iter()
+                    .map(|stats| (stats.policy.as_str(), stats))
+                    .collect::<HashMap<&str, &SlmPolicyStats>>()
+            })
+            .unwrap_or_default();
+
+        for (policy_id, policy) in self.policies.iter().flat_map(|policies| policies.0.iter()) {
+            let mut map = Map::new();
+            let _ = map.insert("policy".into(), json!(policy_id));
+            let _ = map.insert("repository".into(), json!(policy.policy.repository));
+
+            if let Some(ref last_success) = policy.last_success {
+                insert_event(
+                    &mut map,
+                    "policy_last_success",
+                    last_success.time,
+                    now_millis,
+                );
+
+                if let Some(start_time) = last_success.start_time {
+                    let _ = map.insert(
+                        "policy_last_success_duration_millis".into(),
+                        json!(last_success.time - start_time),
+                    );
+                }
+            }
+
+            if let Some(ref last_failure) = policy.last_failure {
+                insert_event(
+                    &mut map,
+                    "policy_last_failure",
+                    last_failure.time,
+                    now_millis,
+                );
+            }
+
+            if let Some(stats) = policy_stats.get(policy_id.as_str()) {
+                let _ = map.insert(
+                    "policy_snapshots_taken".into(),
+                    json!(stats.snapshots_taken),
+                );
+                let _ = map.insert(
+                    "policy_snapshots_failed".into(),
+                    json!(stats.snapshots_failed),
+                );
+                let _ = map.insert(
+                    "policy_snapshots_deleted".into(),
+                    json!(stats.snapshots_deleted),
+                );
+                let _ = map.insert(
+                    "policy_snapshot_deletion_failures".into(),
+                    json!(stats.snapshot_deletion_failures),
+                );
+            }
+
+            values.push(Value::Object(map));
+        }
+
+        if let Some(stats) = self.stats {
+            // Human readable durations, e.g.: "retention_deletion_time": "1.4s"
+            // would become labels
+            let retention = stats
+                .retention
+                .into_iter()
+                .filter(|(_, value)| value.is_number())
+                .map(|(key, value)| (format!("slm_{}", key), value))
+                .collect::<Map<String, Value>>();
+
+            values.push(Value::Object(retention));
+        }
+
+        let mut repositories: BTreeMap<&str, RepositorySnapshots> = BTreeMap::new();
+
+        // Snapshots of SLM managed repositories are not listed, their last
+        // snapshots are reported by policies
+        for policy in self
+            .policies
+            .iter()
+            .flat_map(|policies| policies.0.values())
+        {
+            let repository = repositories.entry(&policy.policy.repository).or_default();
+
+            if let Some(ref last_success) = policy.last_success {
+                repository.success(Success {
+                    time: last_success.time,
+                    duration: last_success
+                        .start_time
+                        .map(|start_time| last_success.time - start_time),
+                });
+            }
+
+            if let Some(ref last_failure) = policy.last_failure {
+                repository.failure(last_failure.time);
+            }
+        }
+
+        for snapshot in self.snapshots.iter() {
+            let repository = repositories.entry(&snapshot.repository).or_default();
+
+            *repository.statuses.entry(&snapshot.status).or_insert(0) += 1;
+
+            match snapshot.status.as_str() {
+                "SUCCESS" => {
+                    if let Some(end_epoch) = parse_epoch(&snapshot.end_epoch) {
+                        repository.success(Success {
+                            time: end_epoch * 1000,
+                            duration: parse_epoch(&snapshot.duration),
+                        });
+                    }
+                }
+                "FAILED" | "PARTIAL" => {
+                    if let Some(end_epoch) = parse_epoch(&snapshot.end_epoch)
+                        .or_else(|| parse_epoch(&snapshot.start_epoch))
+                    {
+                        repository.failure(end_epoch * 1000);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (name, repository) in repositories.into_iter() {
+            let mut map = Map::new();
+            let _ = map.insert("repository".into(), json!(name));
+
+            if let Some(success) = repository.last_success {
+                insert_event(
+                    &mut map,
+                    "repository_last_success",
+                    success.time,
+                    now_millis,
+                );
+
+                if let Some(duration) = success.duration {
+                    let _ = map.insert(
+                        "repository_last_success_duration_millis".into(),
+                        json!(duration),
+                    );
+                }
+            }
+
+            if let Some(time) = repository.last_failure {
+                insert_event(&mut map, "repository_last_failure", time, now_millis);
+            }
+
+            values.push(Value::Object(map));
+
+            values.extend(repository.statuses.into_iter().map(|(status, count)| {
+                json!({
+                    "repository": name,
+                    "status": status,
+                    "repository_snapshots": count,
+                })
+            }));
+        }
+
+        for snapshot in self.status.snapshots.into_iter() {
+            let mut map = snapshot
+                .shards_stats
+                .into_iter()
+                .map(|(key, value)| (format!("in_progress_shards_{}", key), value))
+                .collect::<Map<String, Value>>();
+
+            let _ = map.insert("repository".into(), json!(snapshot.repository));
+            let _ = map.insert("snapshot".into(), json!(snapshot.snapshot));
+            let _ = map.insert("state".into(), json!(snapshot.state));
+
+            values.push(Value::Object(map));
+        }
+
+        values
+    }
+}
@@ -0,0 +1,231 @@
+use elasticsearch::cat::CatSnapshotsParts;
+use elasticsearch::params::Time;
+use elasticsearch::slm::SlmGetLifecycleParts;
+use elasticsearch::snapshot::{SnapshotGetRepositoryParts, SnapshotStatusParts};
+use std::collections::HashMap;
+
+use super::responses::{
+    CatSnapshot, SlmPoliciesResponse, SlmStatsResponse, SnapshotStatusResponse, SnapshotsResponses,
+};
+
+pub(crate) const SUBSYSTEM: &str = "snapshots";
+
+/// SLM is not available on OSS distribution and requires manage_slm privilege,
+/// snapshot metrics are still collected without it
+fn slm_unavailable<T>(
+    result: Result<T, elasticsearch::Error>,
+) -> Result<Option<T>, elasticsearch::Error> {
+    match result {
+        Ok(response) => Ok(Some(response)),
+        Err(e) if e.status_code().is_some() => {
+            debug!("{} SLM is not available err {}", SUBSYSTEM, e);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/snapshot-lifecycle-management-api.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let timeout = exporter.options().timeout_for_subsystem(SUBSYSTEM);
+    let policies = async {
+        exporter
+            .send(|client| async move {
+                client
+                    .slm()
+                    .get_lifecycle(SlmGetLifecycleParts::None)
+                    .request_timeout(timeout)
+                    .send()
+                    .await
+            })
+            .await?
+            .error_for_status_code()?
+            .json::<SlmPoliciesResponse>()
+            .await
+    };
+
+    let stats = async {
+        exporter
+            .send(|client| async move {
+                client
+                    .slm()
+                    .get_stats()
+                    .request_timeout(timeout)
+                    .send()
+                    .await
+            })
+            .await?
+            .error_for_status_code()?
+            .json::<SlmStatsResponse>()
+            .await
+    };
+
+    let policies = slm_unavailable(policies.await)?;
+    let stats = slm_unavailable(stats.await)?;
+
+    let repositories = exporter
+        .send(|client| async move {
+            client
+                .snapshot()
+                .get_repository(SnapshotGetRepositoryParts::None)
+                .request_timeout(timeout)
+                .send()
+                .await
+        })
+        .await?
+        .json::<HashMap<String, Value>>()
+        .await?;
+
+    // Last snapshots of SLM managed repositories are taken from SLM policies,
+    // snapshots are listed only for the rest of repositories as listing cost
+    // grows with snapshot history
+    let unmanaged = repositories
+        .keys()
+        .filter(
+            |repository| !matches!(policies, Some(ref policies) if policies.manages(repository)),
+        )
+        .map(|repository| repository.as_str())
+        .collect::<Vec<&str>>();
+
+    let snapshots = if unmanaged.is_empty() {
+        Vec::new()
+    } else {
+        let unmanaged = &unmanaged;
+
+        exporter
+            .send(|client| async move {
+                client
+                    .cat()
+                    .snapshots(CatSnapshotsParts::Repository(unmanaged))
+                    .format("json")
+                    .h(&[
+                        "repository",
+                        "status",
+                        "start_epoch",
+                        "end_epoch",
+                        "duration",
+                    ])
+                    .time(Time::Ms)
+                    .ignore_unavailable(true)
+                    .request_timeout(timeout)
+                    .send()
+                    .await
+            })
+            .await?
+            .json::<Vec<CatSnapshot>>()
+            .await?
+    };
+
+    let status = exporter
+        .send(|client| async move {
+            client
+                .snapshot()
+                .status(SnapshotStatusParts::None)
+                .request_timeout(timeout)
+                .send()
+                .await
+        })
+        .await?
+        .json::<SnapshotStatusResponse>()
+        .await?;
+
+    let responses = SnapshotsResponses {
+        policies,
+        stats,
+        snapshots,
+        status,
+    };
+
+    Ok(metric::from_values(
+        responses.into_values(chrono::Utc::now().timestamp_millis()),
+    ))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_snapshots() {
+    use serde_json::json;
+
+    let responses = SnapshotsResponses {
+        policies: Some(
+            serde_json::from_str(include_str!("../../tests/files/slm_policy.json"))
+                .expect("valid json"),
+        ),
+        stats: Some(
+            serde_json::from_str(include_str!("../../tests/files/slm_stats.json"))
+                .expect("valid json"),
+        ),
+        snapshots: serde_json::from_str(include_str!("../../tests/files/cat_snapshots.json"))
+            .expect("valid json"),
+        status: serde_json::from_str(include_str!("../../tests/files/snapshot_status.json"))
+            .expect("valid json"),
+    };
+
+    // 2021-08-21T12:00:00Z
+    let values = responses.into_values(1_629_547_200_000);
+
+    assert!(values.contains(&json!({
+        "policy": "nightly-snapshots",
+        "repository": "s3-backups",
+        "policy_last_success_timestamp_seconds": 1_629_504_012,
+        "policy_last_success_age_millis": 43_188_000,
+        "policy_last_success_duration_millis": 12_000,
+        "policy_last_failure_timestamp_seconds": 1_629_417_605,
+        "policy_last_failure_age_millis": 129_595_000,
+        "policy_snapshots_taken": 32,
+        "policy_snapshots_failed": 1,
+        "policy_snapshots_deleted": 2,
+        "policy_snapshot_deletion_failures": 0,
+    })));
+
+    let retention = values
+        .iter()
+        .find(|value| value.get("slm_retention_runs").is_some())
+        .expect("retention value");
+    assert_eq!(retention["slm_retention_deletion_time_millis"], 1_404);
+    // Human readable duration is dropped
+    assert!(retention.get("slm_retention_deletion_time").is_none());
+
+    // SLM managed repository is summarized from its policies
+    assert!(values.contains(&json!({
+        "repository": "s3-backups",
+        "repository_last_success_timestamp_seconds": 1_629_504_012,
+        "repository_last_success_age_millis": 43_188_000,
+        "repository_last_success_duration_millis": 12_000,
+        "repository_last_failure_timestamp_seconds": 1_629_417_605,
+        "repository_last_failure_age_millis": 129_595_000,
+    })));
+    assert!(!values
+        .iter()
+        .any(|value| value["repository"] == "s3-backups" && value.get("status").is_some()));
+
+    // Repository without SLM policy is summarized from listed snapshots
+    assert!(values.contains(&json!({
+        "repository": "fs-backups",
+        "repository_last_success_timestamp_seconds": 1_629_504_012,
+        "repository_last_success_age_millis": 43_188_000,
+        "repository_last_success_duration_millis": 12_000,
+        "repository_last_failure_timestamp_seconds": 1_629_417_605,
+        "repository_last_failure_age_millis": 129_595_000,
+    })));
+    assert!(values.contains(&json!({
+        "repository": "fs-backups",
+        "status": "SUCCESS",
+        "repository_snapshots": 2,
+    })));
+    assert!(values.contains(&json!({
+        "repository": "s3-backups",
+        "snapshot": "nightly-snap-2021.08.21-xyz",
+        "state": "STARTED",
+        "in_progress_shards_done": 120,
+        "in_progress_shards_failed": 0,
+        "in_progress_shards_finalizing": 0,
+        "in_progress_shards_initializing": 0,
+        "in_progress_shards_started": 30,
+        "in_progress_shards_total": 150,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
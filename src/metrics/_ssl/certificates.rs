@@ -0,0 +1,57 @@
+use super::responses::SslCertificatesResponse;
+
+pub(crate) const SUBSYSTEM: &str = "ssl_certificates";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/security-api-ssl.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .ssl()
+                .certificates()
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response
+        .json::<SslCertificatesResponse>()
+        .await?
+        .into_values(chrono::Utc::now().timestamp_millis());
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[test]
+fn test_ssl_certificates() {
+    use serde_json::json;
+
+    let certificates: SslCertificatesResponse =
+        serde_json::from_str(include_str!("../../tests/files/ssl_certificates.json"))
+            .expect("valid json");
+
+    // 2021-08-21T12:00:00Z
+    let values = certificates.into_values(1_629_547_200_000);
+
+    // CA certificate listed under two aliases is exported once
+    assert_eq!(values.len(), 2);
+    assert!(values.contains(&json!({
+        "path": "certs/elastic-certificates.p12",
+        "subject": "CN=instance",
+        "serial": "a20f0ee901e8f69dc633ff633e5cd5437cdb4137",
+        "expires_in_seconds": 86_400,
+    })));
+    // Expired certificate
+    assert!(values.contains(&json!({
+        "path": "certs/elastic-certificates.p12",
+        "subject": "CN=Elastic Certificate Tool Autogenerated CA",
+        "serial": "a20f0ee901e8f64f33ff633e5cd5437cdb4137",
+        "expires_in_seconds": -3_600,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
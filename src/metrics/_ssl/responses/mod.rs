@@ -0,0 +1,58 @@
+use chrono::DateTime;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// SSL certificates response
+#[derive(Debug, Deserialize)]
+pub(crate) struct SslCertificatesResponse(Vec<SslCertificate>);
+
+#[derive(Debug, Deserialize)]
+struct SslCertificate {
+    path: String,
+    subject_dn: String,
+    serial_number: String,
+    /// Date string, e.g.: 2022-06-08T10:17:44.000Z
+    expiry: String,
+}
+
+impl SslCertificatesResponse {
+    /// Seconds until expiry of each certificate, negative for expired certificates.
+    /// Certificate listed under several aliases of the same keystore is exported once
+    pub(crate) fn into_values(self, now_millis: i64) -> Vec<Value> {
+        let mut certificates: BTreeMap<(String, String, String), i64> = BTreeMap::new();
+
+        for certificate in self.0 {
+            let expiry = match DateTime::parse_from_rfc3339(&certificate.expiry) {
+                Ok(expiry) => expiry.timestamp_millis(),
+                Err(e) => {
+                    error!(
+                        "ssl certificate {} expiry {} err {}",
+                        certificate.path, certificate.expiry, e
+                    );
+                    continue;
+                }
+            };
+
+            let _ = certificates.insert(
+                (
+                    certificate.path,
+                    certificate.subject_dn,
+                    certificate.serial_number,
+                ),
+                (expiry - now_millis) / 1000,
+            );
+        }
+
+        certificates
+            .into_iter()
+            .map(|((path, subject, serial), expires_in)| {
+                json!({
+                    "path": path,
+                    "subject": subject,
+                    "serial": serial,
+                    "expires_in_seconds": expires_in,
+                })
+            })
+            .collect()
+    }
+}
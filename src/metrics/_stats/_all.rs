@@ -6,12 +6,15 @@ pub(crate) const SUBSYSTEM: &str = "stats";
 
 async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
     let response = exporter
-        .client()
-        .indices()
-        .stats(IndicesStatsParts::None)
-        .fields(&exporter.options().query_fields_for_subsystem(SUBSYSTEM))
-        .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
-        .send()
+        .send(|client| async move {
+            client
+                .indices()
+                .stats(IndicesStatsParts::None)
+                .fields(&exporter.options().query_fields_for_subsystem(SUBSYSTEM))
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
         .await?;
 
     let values = response
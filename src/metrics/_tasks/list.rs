@@ -0,0 +1,78 @@
+use super::responses::TasksResponse;
+
+pub(crate) const SUBSYSTEM: &str = "tasks";
+
+// https://www.elastic.co/guide/en/elasticsearch/reference/current/tasks.html
+async fn metrics(exporter: &Exporter) -> Result<Vec<Metrics>, elasticsearch::Error> {
+    let response = exporter
+        .send(|client| async move {
+            client
+                .tasks()
+                .list()
+                .detailed(false)
+                .request_timeout(exporter.options().timeout_for_subsystem(SUBSYSTEM))
+                .send()
+                .await
+        })
+        .await?;
+
+    let values = response
+        .json::<TasksResponse>()
+        .await?
+        .into_values(
+            exporter.nodes_metadata(),
+            exporter.options().exporter_tasks_long_running_threshold,
+        )
+        .await;
+
+    Ok(metric::from_values(values))
+}
+
+crate::poll_metrics!();
+
+#[tokio::test]
+async fn test_tasks() {
+    use crate::metadata::NodeData;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use tokio::sync::RwLock;
+
+    let tasks: TasksResponse =
+        serde_json::from_str(include_str!("../../tests/files/tasks.json")).expect("valid json");
+
+    let mut metadata = HashMap::new();
+    let _ = metadata.insert(
+        "oTUltX4IQMOUUVeiohTt8A".to_string(),
+        NodeData {
+            name: "m1-data-1.example.com".into(),
+            ..Default::default()
+        },
+    );
+
+    let values = tasks
+        .into_values(&RwLock::new(metadata), Duration::from_secs(60))
+        .await;
+
+    // Exporter own tasks list requests are skipped
+    assert_eq!(values.len(), 3);
+    assert!(values.contains(&json!({
+        "action": "indices:data/write/reindex",
+        "name": "m1-data-1.example.com",
+        "cancellable": "true",
+        "count": 2,
+        "long_running": 1,
+        "max_running_time_millis": 3_723_000,
+    })));
+    // Node missing from metadata falls back to node name of response
+    assert!(values.contains(&json!({
+        "action": "indices:data/write/bulk",
+        "name": "m1-data-2.example.com",
+        "cancellable": "false",
+        "count": 1,
+        "long_running": 0,
+        "max_running_time_millis": 12,
+    })));
+
+    let metrics = metric::from_values(values);
+    assert!(!metrics.is_empty());
+}
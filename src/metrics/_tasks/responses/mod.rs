@@ -0,0 +1,98 @@
+use serde_json::{json, Value};
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::metadata::IdToMetadata;
+
+/// Actions of exporter own requests, reported on every poll
+const SKIP_ACTIONS: &[&str] = &["cluster:monitor/tasks/lists"];
+
+/// Tasks response grouped by nodes
+#[derive(Debug, Deserialize)]
+pub(crate) struct TasksResponse {
+    #[serde(default)]
+    nodes: HashMap<String, TasksNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TasksNode {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    tasks: HashMap<String, Task>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Task {
+    action: String,
+    #[serde(default)]
+    cancellable: bool,
+    #[serde(default)]
+    running_time_in_nanos: u64,
+}
+
+/// Tasks of the same action, node and cancellable flag
+#[derive(Debug, Default)]
+struct TasksGroup {
+    count: u64,
+    long_running: u64,
+    max_running_time: Duration,
+}
+
+impl TasksResponse {
+    /// Aggregate running tasks by action, node name and cancellable flag,
+    /// tasks running longer than threshold are counted separately
+    pub(crate) async fn into_values(
+        self,
+        metadata: &IdToMetadata,
+        long_running_threshold: Duration,
+    ) -> Vec<Value> {
+        let metadata_read = metadata.read().await;
+
+        let mut groups: BTreeMap<(String, String, bool), TasksGroup> = BTreeMap::new();
+
+        for (node_id, node) in self.nodes.into_iter() {
+            let name = metadata_read
+                .get(&node_id)
+                .map(|node_data| node_data.name.clone())
+                .or(node.name)
+                .unwrap_or(node_id);
+
+            for task in node.tasks.into_values() {
+                if SKIP_ACTIONS
+                    .iter()
+                    .any(|action| task.action.starts_with(action))
+                {
+                    continue;
+                }
+
+                let running_time = Duration::from_nanos(task.running_time_in_nanos);
+
+                let group = groups
+                    .entry((task.action, name.clone(), task.cancellable))
+                    .or_default();
+
+                group.count += 1;
+                group.max_running_time = cmp::max(group.max_running_time, running_time);
+                if running_time > long_running_threshold {
+                    group.long_running += 1;
+                }
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|((action, name, cancellable), group)| {
+                json!({
+                    "action": action,
+                    "name": name,
+                    "cancellable": cancellable.to_string(),
+                    "count": group.count,
+                    "long_running": group.long_running,
+                    "max_running_time_millis": group.max_running_time.as_millis() as u64,
+                })
+            })
+            .collect()
+    }
+}
@@ -1,12 +1,18 @@
+pub(crate) mod _canary;
 pub(crate) mod _cat;
+pub(crate) mod _ccr;
 pub(crate) mod _cluster;
+pub(crate) mod _data_stream;
+pub(crate) mod _ilm;
+pub(crate) mod _license;
+pub(crate) mod _migration;
 pub(crate) mod _nodes;
+pub(crate) mod _search;
+pub(crate) mod _settings;
+pub(crate) mod _snapshot;
+pub(crate) mod _ssl;
 pub(crate) mod _stats;
-
-// TODO: add metrics of
-// - https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-pending.html
-// - https://www.elastic.co/guide/en/elasticsearch/reference/current/tasks.html
-// - https://www.elastic.co/guide/en/elasticsearch/reference/current/cluster-state.html
+pub(crate) mod _tasks;
 
 /// Convenience macro to poll metrics
 #[macro_export]
@@ -22,11 +28,11 @@ macro_rules! poll_metrics {
         use crate::metric::{self, Metrics};
         use crate::Exporter;
 
-        #[allow(unused)]
-        pub(crate) async fn poll(exporter: Exporter) {
+        fn new_collection(exporter: &Exporter) -> Collection {
             let options = exporter.options();
 
-            let mut collection = Collection::new(SUBSYSTEM, options.clone());
+            let mut collection =
+                Collection::new(SUBSYSTEM, (*options).clone(), exporter.registry().clone());
             // Common to all /_cat metrics
             collection.const_labels = exporter.const_labels();
 
@@ -42,6 +48,28 @@ macro_rules! poll_metrics {
                 collection.include_labels = include_labels.clone();
             }
 
+            collection
+        }
+
+        /// Collect subsystem metrics once into exporter registry, metrics are
+        /// unregistered when returned collection is dropped
+        #[allow(unused)]
+        pub(crate) async fn probe(exporter: &Exporter) -> Result<Collection, elasticsearch::Error> {
+            let mut collection = new_collection(exporter);
+
+            for metric in metrics(exporter).await?.into_iter() {
+                let _ = collection.collect(metric);
+            }
+
+            Ok(collection)
+        }
+
+        #[allow(unused)]
+        pub(crate) async fn poll(exporter: Exporter) {
+            let options = exporter.options();
+
+            let mut collection = new_collection(&exporter);
+
             let start =
                 tokio::time::Instant::now() + Duration::from_millis(Exporter::random_delay());
 
@@ -86,6 +114,11 @@ macro_rules! poll_metrics {
                     }
                     Err(e) => {
                         error!("poll {} metrics err {}", collection.subsystem(), e);
+
+                        // No response, node might be unreachable
+                        if e.status_code().is_none() {
+                            exporter.connection_failed();
+                        }
                     }
                 }
 
@@ -1,14 +1,74 @@
+use serde_json::Value;
 use std::fmt;
 use std::time::Duration;
 use url::Url;
 
+use crate::credentials::{redact_url, Auth, Tls};
 use crate::{metrics, CollectionLabels, ExporterMetricsSwitch, ExporterPollIntervals};
 
+/// Cluster options overriding exporter options in multi-cluster mode
+#[derive(Debug, Clone)]
+pub struct ClusterOptions {
+    /// Elasticsearch cluster url
+    pub elasticsearch_url: Url,
+    /// Cluster label, defaults to cluster name reported by Elasticsearch
+    pub elasticsearch_cluster_name: Option<String>,
+    /// Additional seed urls of the same cluster
+    pub elasticsearch_seed_urls: Vec<Url>,
+    /// Cluster authentication, exporter authentication is used if not set
+    pub elasticsearch_auth: Option<Auth>,
+    /// Cluster client TLS configuration, exporter TLS configuration is used if not set
+    pub elasticsearch_tls: Option<Tls>,
+    /// Cluster metrics switch, exporter metrics switch is used if empty
+    pub exporter_metrics_enabled: ExporterMetricsSwitch,
+    /// Cluster poll intervals, merged over exporter poll intervals
+    pub exporter_poll_intervals: ExporterPollIntervals,
+    /// Cluster subsystem timeouts, merged over exporter subsystem timeouts
+    pub elasticsearch_subsystem_timeouts: ExporterPollIntervals,
+}
+
+/// User-defined search exported by queries subsystem
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryOptions {
+    /// Query name, metrics are labeled with it
+    pub name: String,
+    /// Index pattern searched
+    pub index: String,
+    /// Query DSL, all documents are matched if empty
+    #[serde(default)]
+    pub query: Option<Value>,
+    /// Aggregations, bucket keys of terms or date histogram aggregations become labels
+    #[serde(default)]
+    pub aggregations: Option<Value>,
+    /// Interval between searches, defaults to queries subsystem poll interval
+    #[serde(default, with = "humantime_serde")]
+    pub interval: Option<Duration>,
+}
+
 /// Elasticsearch exporter options
 #[derive(Debug, Clone)]
 pub struct ExporterOptions {
     /// Elasticsearch cluster url
     pub elasticsearch_url: Url,
+    /// Cluster label, defaults to cluster name reported by Elasticsearch
+    pub elasticsearch_cluster_name: Option<String>,
+    /// Elasticsearch clusters scraped by single exporter process,
+    /// each cluster gets its own exporter instance
+    pub elasticsearch_clusters: Vec<ClusterOptions>,
+    /// Elasticsearch authentication
+    pub elasticsearch_auth: Option<Auth>,
+    /// Additional seed urls of the same cluster, requests are round-robined
+    /// across alive nodes of elasticsearch_url and seed urls
+    pub elasticsearch_seed_urls: Vec<Url>,
+    /// Discover cluster nodes via /_nodes/http and use them instead of seed urls
+    pub elasticsearch_sniff: bool,
+    /// Interval of connection pool health checks and sniffing
+    pub elasticsearch_pool_check_interval: Duration,
+    /// Elasticsearch client TLS configuration
+    pub elasticsearch_tls: Tls,
+    /// Interval of checking credential and certificate files for changes
+    pub elasticsearch_credentials_refresh_interval: Duration,
     /// Global HTTP request timeout
     pub elasticsearch_global_timeout: Duration,
     /// Elasticsearch /_nodes/stats fields comma-separated list or
@@ -36,6 +96,31 @@ pub struct ExporterOptions {
     pub exporter_metrics_enabled: ExporterMetricsSwitch,
     /// Exporter metadata refresh interval
     pub exporter_metadata_refresh_interval: Duration,
+    /// Tasks running longer than threshold are counted as long running
+    pub exporter_tasks_long_running_threshold: Duration,
+    /// Maximum number of unassigned shards explained per poll
+    pub exporter_allocation_explain_max_shards: usize,
+    /// Export per processor stats of ingest pipelines in nodes_stats subsystem
+    pub exporter_nodes_stats_ingest_processors: bool,
+    /// Timestamp field of index freshness probe
+    pub exporter_index_freshness_timestamp_field: String,
+    /// Recent documents window of index freshness probe
+    pub exporter_index_freshness_window: Duration,
+    /// User-defined searches of queries subsystem
+    pub exporter_queries: Vec<QueryOptions>,
+    /// Maximum number of aggregation buckets exported per query
+    pub exporter_queries_max_buckets: usize,
+    /// Index canary documents are written to
+    pub exporter_canary_index: String,
+    /// Number of shards canary index is created with
+    pub exporter_canary_shards: usize,
+    /// Time canary document must become visible to search within
+    pub exporter_canary_visibility_timeout: Duration,
+    /// Age of canary documents removed from canary index
+    pub exporter_canary_retention: Duration,
+    /// Cluster settings exported by cluster_settings subsystem,
+    /// keys ending with * match by prefix
+    pub exporter_cluster_settings: Vec<String>,
 
     /// Metrics polling interval
     pub exporter_poll_default_interval: Duration,
@@ -49,18 +134,119 @@ pub struct ExporterOptions {
 }
 
 impl ExporterOptions {
+    /// Exporter options of each configured cluster, without configured clusters
+    /// exporter options are returned as is
+    pub fn clusters(&self) -> Vec<ExporterOptions> {
+        if self.elasticsearch_clusters.is_empty() {
+            return vec![self.clone()];
+        }
+
+        self.elasticsearch_clusters
+            .iter()
+            .map(|cluster| {
+                let mut options = self.clone();
+
+                options.elasticsearch_clusters = Vec::new();
+                options.elasticsearch_url = cluster.elasticsearch_url.clone();
+                options.elasticsearch_cluster_name = cluster.elasticsearch_cluster_name.clone();
+                options.elasticsearch_seed_urls = cluster.elasticsearch_seed_urls.clone();
+
+                if cluster.elasticsearch_auth.is_some() {
+                    options.elasticsearch_auth = cluster.elasticsearch_auth.clone();
+                }
+
+                if let Some(ref tls) = cluster.elasticsearch_tls {
+                    options.elasticsearch_tls = tls.clone();
+                }
+
+                if !cluster.exporter_metrics_enabled.is_empty() {
+                    options.exporter_metrics_enabled = cluster.exporter_metrics_enabled.clone();
+                }
+
+                options
+                    .exporter_poll_intervals
+                    .extend(cluster.exporter_poll_intervals.clone());
+                options
+                    .elasticsearch_subsystem_timeouts
+                    .extend(cluster.elasticsearch_subsystem_timeouts.clone());
+
+                options
+            })
+            .collect()
+    }
+
+    /// Seed urls of connection pool
+    pub(crate) fn elasticsearch_urls(&self) -> Vec<Url> {
+        let mut urls = vec![self.elasticsearch_url.clone()];
+        urls.extend(self.elasticsearch_seed_urls.iter().cloned());
+        urls
+    }
+
+    /// Options of probed target, credentials and client certificate are kept only
+    /// if target is one of configured clusters, so probes can't leak them elsewhere
+    pub(crate) fn probe_options(&self, target: &Url) -> ExporterOptions {
+        let origin = |url: &Url| {
+            (
+                url.scheme().to_string(),
+                url.host_str().map(String::from),
+                url.port_or_known_default(),
+            )
+        };
+
+        let mut options = self
+            .clusters()
+            .into_iter()
+            .find(|cluster| {
+                cluster
+                    .elasticsearch_urls()
+                    .iter()
+                    .any(|url| origin(url) == origin(target))
+            })
+            .unwrap_or_else(|| {
+                let mut options = self.clone();
+                options.elasticsearch_auth = None;
+                options.elasticsearch_tls = Tls::default();
+                options
+            });
+
+        options.elasticsearch_url = target.clone();
+        options.elasticsearch_cluster_name = None;
+        options.elasticsearch_clusters = Vec::new();
+        options.elasticsearch_seed_urls = Vec::new();
+        options.elasticsearch_sniff = false;
+        options
+    }
+
+    /// Are credentials or certificates read from files which may change
+    pub(crate) fn has_credential_files(&self) -> bool {
+        self.elasticsearch_tls.has_files()
+            || self
+                .elasticsearch_auth
+                .as_ref()
+                .map(|auth| auth.has_files())
+                .unwrap_or(false)
+    }
+
     /// Enable metadata refresh?
     pub(crate) fn enable_metadata_refresh(&self) -> bool {
-        let cluster_subsystems = Self::nodes_subsystems();
+        let metadata_subsystems = Self::metadata_subsystems();
 
         self.exporter_metrics_enabled
             .iter()
-            .any(|(k, v)| cluster_subsystems.contains(&k.as_str()) && *v)
+            .any(|(k, v)| metadata_subsystems.contains(&k.as_str()) && *v)
+    }
+
+    /// Subsystems labeling metrics by node metadata
+    pub(crate) fn metadata_subsystems() -> Vec<&'static str> {
+        [Self::nodes_subsystems(), Self::tasks_subsystems()].concat()
     }
 
     /// Check if metric is enabled
-    pub fn is_metric_enabled(&self, subsystem: &'static str) -> bool {
-        self.exporter_metrics_enabled.contains_key(subsystem)
+    pub fn is_metric_enabled(&self, subsystem: &str) -> bool {
+        self.exporter_metrics_enabled
+            .get(subsystem)
+            .copied()
+            .unwrap_or(false)
     }
 
     /// ?fields= parameters for subsystems
@@ -87,6 +273,154 @@ impl ExporterOptions {
             .unwrap_or(&self.elasticsearch_global_timeout)
     }
 
+    /// Options subsystem poller and its collection are started with
+    fn subsystem_options(&self, subsystem: &str) -> SubsystemOptions<'_> {
+        SubsystemOptions {
+            enabled: self.is_metric_enabled(subsystem),
+            poll_interval: self
+                .exporter_poll_intervals
+                .get(subsystem)
+                .unwrap_or(&self.exporter_poll_default_interval),
+            lifetime: self
+                .exporter_metrics_lifetime_interval
+                .get(subsystem)
+                .unwrap_or(&self.exporter_metrics_lifetime_default_interval),
+            timeout: self
+                .elasticsearch_subsystem_timeouts
+                .get(subsystem)
+                .unwrap_or(&self.elasticsearch_global_timeout),
+            skip_zero_metrics: self.exporter_skip_zero_metrics,
+            skip_labels: self.exporter_skip_labels.get(subsystem),
+            include_labels: self.exporter_include_labels.get(subsystem),
+            skip_metrics: self.exporter_skip_metrics.get(subsystem),
+            path_parameters: self.elasticsearch_path_parameters.get(subsystem),
+            query_fields: self.elasticsearch_query_fields.get(subsystem),
+            specific: self.specific_options(subsystem),
+        }
+    }
+
+    /// Options used by single subsystem only
+    fn specific_options(&self, subsystem: &str) -> SpecificOptions<'_> {
+        use metrics::{_canary::probe, _cluster, _nodes, _search, _tasks};
+
+        match subsystem {
+            probe::SUBSYSTEM => SpecificOptions::Canary {
+                index: &self.exporter_canary_index,
+                shards: self.exporter_canary_shards,
+                visibility_timeout: &self.exporter_canary_visibility_timeout,
+                retention: &self.exporter_canary_retention,
+            },
+            _cluster::allocation_explain::SUBSYSTEM => {
+                SpecificOptions::AllocationExplain(self.exporter_allocation_explain_max_shards)
+            }
+            _cluster::settings::SUBSYSTEM => {
+                SpecificOptions::ClusterSettings(&self.exporter_cluster_settings)
+            }
+            _nodes::stats::SUBSYSTEM => {
+                SpecificOptions::NodesStats(self.exporter_nodes_stats_ingest_processors)
+            }
+            _search::freshness::SUBSYSTEM => SpecificOptions::IndexFreshness {
+                timestamp_field: &self.exporter_index_freshness_timestamp_field,
+                window: &self.exporter_index_freshness_window,
+            },
+            _search::queries::SUBSYSTEM => SpecificOptions::Queries {
+                queries: &self.exporter_queries,
+                max_buckets: self.exporter_queries_max_buckets,
+            },
+            _tasks::list::SUBSYSTEM => {
+                SpecificOptions::Tasks(&self.exporter_tasks_long_running_threshold)
+            }
+            _ => SpecificOptions::None,
+        }
+    }
+
+    /// Subsystems of which poller options differ from given options
+    pub fn changed_subsystems(&self, other: &Self) -> Vec<&'static str> {
+        Self::subsystems()
+            .into_iter()
+            .filter(|subsystem| {
+                self.subsystem_options(subsystem) != other.subsystem_options(subsystem)
+            })
+            .collect()
+    }
+
+    /// Options differing from given options which are applied only on exporter start,
+    /// e.g.: Elasticsearch client settings
+    pub fn restart_required(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+
+        macro_rules! compare {
+            ($($field:ident),+) => {
+                $(
+                    if self.$field != other.$field {
+                        changed.push(stringify!($field));
+                    }
+                )+
+            };
+        }
+
+        compare!(
+            elasticsearch_url,
+            elasticsearch_cluster_name,
+            elasticsearch_auth,
+            elasticsearch_seed_urls,
+            elasticsearch_sniff,
+            elasticsearch_pool_check_interval,
+            elasticsearch_tls,
+            elasticsearch_credentials_refresh_interval,
+            elasticsearch_global_timeout
+        );
+
+        if self.elasticsearch_clusters.len() != other.elasticsearch_clusters.len()
+            || self
+                .elasticsearch_clusters
+                .iter()
+                .zip(other.elasticsearch_clusters.iter())
+                .any(|(cluster, other)| {
+                    cluster.elasticsearch_url != other.elasticsearch_url
+                        || cluster.elasticsearch_cluster_name != other.elasticsearch_cluster_name
+                        || cluster.elasticsearch_seed_urls != other.elasticsearch_seed_urls
+                        || cluster.elasticsearch_auth != other.elasticsearch_auth
+                        || cluster.elasticsearch_tls != other.elasticsearch_tls
+                })
+        {
+            changed.push("elasticsearch_clusters");
+        }
+
+        changed
+    }
+
+    /// Subsystems which are not probed, canary writes documents and queries run
+    /// configured searches, so both run only from pollers of configured clusters
+    pub fn probe_excluded_subsystems() -> &'static [&'static str] {
+        &[
+            metrics::_canary::probe::SUBSYSTEM,
+            metrics::_search::queries::SUBSYSTEM,
+        ]
+    }
+
+    /// All available subsystems
+    pub fn subsystems() -> Vec<&'static str> {
+        [
+            Self::cat_subsystems(),
+            Self::cluster_subsystems(),
+            Self::ccr_subsystems(),
+            Self::canary_subsystems(),
+            Self::data_stream_subsystems(),
+            Self::ilm_subsystems(),
+            Self::license_subsystems(),
+            Self::migration_subsystems(),
+            Self::nodes_subsystems(),
+            Self::search_subsystems(),
+            Self::settings_subsystems(),
+            Self::snapshot_subsystems(),
+            Self::ssl_subsystems(),
+            Self::stats_subsystems(),
+            Self::tasks_subsystems(),
+        ]
+        .concat()
+    }
+
     /// /_cat subsystems
     pub fn cat_subsystems() -> &'static [&'static str] {
         use metrics::_cat::*;
@@ -115,14 +449,97 @@ impl ExporterOptions {
     pub fn cluster_subsystems() -> &'static [&'static str] {
         use metrics::_cluster::*;
 
-        &[health::SUBSYSTEM]
+        &[
+            health::SUBSYSTEM,
+            stats::SUBSYSTEM,
+            pending_tasks::SUBSYSTEM,
+            state::SUBSYSTEM,
+            allocation_explain::SUBSYSTEM,
+            remote_info::SUBSYSTEM,
+            settings::SUBSYSTEM,
+        ]
+    }
+
+    /// /_ccr subsystems
+    pub fn ccr_subsystems() -> &'static [&'static str] {
+        use metrics::_ccr::*;
+
+        &[stats::SUBSYSTEM]
+    }
+
+    /// Synthetic canary subsystems
+    pub fn canary_subsystems() -> &'static [&'static str] {
+        use metrics::_canary::*;
+
+        &[probe::SUBSYSTEM]
+    }
+
+    /// /_data_stream subsystems
+    pub fn data_stream_subsystems() -> &'static [&'static str] {
+        use metrics::_data_stream::*;
+
+        &[stats::SUBSYSTEM]
+    }
+
+    /// /_ilm subsystems
+    pub fn ilm_subsystems() -> &'static [&'static str] {
+        use metrics::_ilm::*;
+
+        &[explain::SUBSYSTEM, indices::SUBSYSTEM]
+    }
+
+    /// /_license subsystems
+    pub fn license_subsystems() -> &'static [&'static str] {
+        use metrics::_license::*;
+
+        &[license::SUBSYSTEM]
+    }
+
+    /// /_migration subsystems
+    pub fn migration_subsystems() -> &'static [&'static str] {
+        use metrics::_migration::*;
+
+        &[deprecations::SUBSYSTEM]
     }
 
     /// /_nodes subsystems
     pub fn nodes_subsystems() -> &'static [&'static str] {
         use metrics::_nodes::*;
 
-        &[usage::SUBSYSTEM, stats::SUBSYSTEM, info::SUBSYSTEM]
+        &[
+            usage::SUBSYSTEM,
+            stats::SUBSYSTEM,
+            info::SUBSYSTEM,
+            versions::SUBSYSTEM,
+        ]
+    }
+
+    /// /_search subsystems
+    pub fn search_subsystems() -> &'static [&'static str] {
+        use metrics::_search::*;
+
+        &[freshness::SUBSYSTEM, queries::SUBSYSTEM]
+    }
+
+    /// /_settings subsystems
+    pub fn settings_subsystems() -> &'static [&'static str] {
+        use metrics::_settings::*;
+
+        &[index_blocks::SUBSYSTEM]
+    }
+
+    /// /_snapshot and /_slm subsystems
+    pub fn snapshot_subsystems() -> &'static [&'static str] {
+        use metrics::_snapshot::*;
+
+        &[snapshots::SUBSYSTEM]
+    }
+
+    /// /_ssl subsystems
+    pub fn ssl_subsystems() -> &'static [&'static str] {
+        use metrics::_ssl::*;
+
+        &[certificates::SUBSYSTEM]
     }
 
     /// /_stats subsystems
@@ -131,6 +548,54 @@ impl ExporterOptions {
 
         &[_all::SUBSYSTEM]
     }
+
+    /// /_tasks subsystems
+    pub fn tasks_subsystems() -> &'static [&'static str] {
+        use metrics::_tasks::*;
+
+        &[list::SUBSYSTEM]
+    }
+}
+
+/// Options subsystem poller is started with, compared on reload
+/// to restart only affected subsystems
+#[derive(Debug, PartialEq)]
+struct SubsystemOptions<'a> {
+    enabled: bool,
+    poll_interval: &'a Duration,
+    lifetime: &'a Duration,
+    timeout: &'a Duration,
+    skip_zero_metrics: bool,
+    skip_labels: Option<&'a Vec<String>>,
+    include_labels: Option<&'a Vec<String>>,
+    skip_metrics: Option<&'a Vec<String>>,
+    path_parameters: Option<&'a Vec<String>>,
+    query_fields: Option<&'a Vec<String>>,
+    specific: SpecificOptions<'a>,
+}
+
+/// Options of single subsystem, e.g.: queries of queries subsystem
+#[derive(Debug, PartialEq)]
+enum SpecificOptions<'a> {
+    None,
+    Canary {
+        index: &'a str,
+        shards: usize,
+        visibility_timeout: &'a Duration,
+        retention: &'a Duration,
+    },
+    AllocationExplain(usize),
+    ClusterSettings(&'a [String]),
+    NodesStats(bool),
+    IndexFreshness {
+        timestamp_field: &'a str,
+        window: &'a Duration,
+    },
+    Queries {
+        queries: &'a [QueryOptions],
+        max_buckets: usize,
+    },
+    Tasks(&'a Duration),
 }
 
 fn switch_to_string(output: &mut String, field: &'static str, switches: &ExporterMetricsSwitch) {
@@ -168,6 +633,43 @@ fn poll_duration_to_string(
     }
 }
 
+fn clusters_to_string(output: &mut String, field: &'static str, clusters: &[ClusterOptions]) {
+    output.push('\n');
+    output.push_str(&format!("{}:", field));
+    for cluster in clusters.iter() {
+        output.push('\n');
+        output.push_str(&format!(" - {}", redact_url(&cluster.elasticsearch_url)));
+
+        if let Some(ref name) = cluster.elasticsearch_cluster_name {
+            output.push_str(&format!(" name: {}", name));
+        }
+
+        for url in cluster.elasticsearch_seed_urls.iter() {
+            output.push_str(&format!(" seed: {}", redact_url(url)));
+        }
+
+        if let Some(ref auth) = cluster.elasticsearch_auth {
+            output.push_str(&format!(" auth: {}", auth));
+        }
+
+        if let Some(ref tls) = cluster.elasticsearch_tls {
+            output.push_str(&format!(" tls: {}", tls));
+        }
+
+        for (k, v) in cluster.exporter_metrics_enabled.iter() {
+            output.push_str(&format!(" {}: {}", k, v));
+        }
+
+        for (k, v) in cluster.exporter_poll_intervals.iter() {
+            output.push_str(&format!(" {} interval: {:?}", k, v));
+        }
+
+        for (k, v) in cluster.elasticsearch_subsystem_timeouts.iter() {
+            output.push_str(&format!(" {} timeout: {:?}", k, v));
+        }
+    }
+}
+
 fn vec_to_string(output: &mut String, field: &'static str, fields: &[&'static str]) {
     output.push('\n');
     output.push_str(&format!("{}:", field));
@@ -192,22 +694,123 @@ impl fmt::Display for ExporterOptions {
             "Available /_cluster subsystems",
             Self::cluster_subsystems(),
         );
+        vec_to_string(
+            &mut output,
+            "Available /_ccr subsystems",
+            Self::ccr_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available canary subsystems",
+            Self::canary_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_data_stream subsystems",
+            Self::data_stream_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_ilm subsystems",
+            Self::ilm_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_license subsystems",
+            Self::license_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_migration subsystems",
+            Self::migration_subsystems(),
+        );
         vec_to_string(
             &mut output,
             "Available /_nodes subsystems",
             Self::nodes_subsystems(),
         );
+        vec_to_string(
+            &mut output,
+            "Available /_search subsystems",
+            Self::search_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_settings subsystems",
+            Self::settings_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_snapshot subsystems",
+            Self::snapshot_subsystems(),
+        );
+        vec_to_string(
+            &mut output,
+            "Available /_ssl subsystems",
+            Self::ssl_subsystems(),
+        );
         vec_to_string(
             &mut output,
             "Available /_stats subsystems",
             Self::stats_subsystems(),
         );
+        vec_to_string(
+            &mut output,
+            "Available /_tasks subsystems",
+            Self::tasks_subsystems(),
+        );
         output.push('\n');
 
         output.push('\n');
         output.push_str("Exporter settings:");
         output.push('\n');
-        output.push_str(&format!("elasticsearch_url: {}", self.elasticsearch_url));
+        output.push_str(&format!(
+            "elasticsearch_url: {}",
+            redact_url(&self.elasticsearch_url)
+        ));
+
+        for url in self.elasticsearch_seed_urls.iter() {
+            output.push('\n');
+            output.push_str(&format!("elasticsearch_seed_url: {}", redact_url(url)));
+        }
+
+        output.push('\n');
+        output.push_str(&format!(
+            "elasticsearch_sniff: {}",
+            self.elasticsearch_sniff
+        ));
+        output.push('\n');
+        output.push_str(&format!(
+            "elasticsearch_pool_check_interval: {:?}",
+            self.elasticsearch_pool_check_interval
+        ));
+
+        if let Some(ref auth) = self.elasticsearch_auth {
+            output.push('\n');
+            output.push_str(&format!("elasticsearch_auth: {}", auth));
+        }
+
+        output.push('\n');
+        output.push_str(&format!("elasticsearch_tls: {}", self.elasticsearch_tls));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "elasticsearch_credentials_refresh_interval: {:?}",
+            self.elasticsearch_credentials_refresh_interval
+        ));
+
+        if let Some(ref name) = self.elasticsearch_cluster_name {
+            output.push('\n');
+            output.push_str(&format!("elasticsearch_cluster_name: {}", name));
+        }
+
+        if !self.elasticsearch_clusters.is_empty() {
+            clusters_to_string(
+                &mut output,
+                "elasticsearch_clusters",
+                &self.elasticsearch_clusters,
+            );
+        }
         output.push('\n');
         output.push_str(&format!(
             "elasticsearch_global_timeout: {:?}",
@@ -279,6 +882,79 @@ impl fmt::Display for ExporterOptions {
             self.exporter_metadata_refresh_interval
         ));
 
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_tasks_long_running_threshold: {:?}",
+            self.exporter_tasks_long_running_threshold
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_allocation_explain_max_shards: {}",
+            self.exporter_allocation_explain_max_shards
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_nodes_stats_ingest_processors: {}",
+            self.exporter_nodes_stats_ingest_processors
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_index_freshness_timestamp_field: {}",
+            self.exporter_index_freshness_timestamp_field
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_index_freshness_window: {:?}",
+            self.exporter_index_freshness_window
+        ));
+
+        output.push('\n');
+        output.push_str("exporter_queries:");
+        for query in self.exporter_queries.iter() {
+            output.push('\n');
+            output.push_str(&format!(" - {}: {}", query.name, query.index));
+        }
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_queries_max_buckets: {}",
+            self.exporter_queries_max_buckets
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_canary_index: {}",
+            self.exporter_canary_index
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_canary_shards: {}",
+            self.exporter_canary_shards
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_canary_visibility_timeout: {:?}",
+            self.exporter_canary_visibility_timeout
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_canary_retention: {:?}",
+            self.exporter_canary_retention
+        ));
+
+        output.push('\n');
+        output.push_str(&format!(
+            "exporter_cluster_settings: {}",
+            self.exporter_cluster_settings.join(",")
+        ));
+
         output.push('\n');
         output.push_str(&format!(
             "exporter_metrics_lifetime_default_interval: {:?}",